@@ -1,4 +1,4 @@
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CondInputKw {
     AllKw,
     CopiesKw,
@@ -103,6 +103,8 @@ pub enum CondInputKw {
     PurgeCacheKw,
     EmptyBundleOnlyKw,
     ReplicaTokenKw,
+    RescHierStrKw,
+    DestRescHierStrKw,
     RegisterAsIntermediateKw,
     StaleAllIntermediateReplicasKw,
     SourceL1DescKw,
@@ -230,6 +232,8 @@ impl From<&str> for CondInputKw {
             "purgeCache" => Self::PurgeCacheKw,
             "emptyBundleOnly" => Self::EmptyBundleOnlyKw,
             "replicaToken" => Self::ReplicaTokenKw,
+            "resc_hier" => Self::RescHierStrKw,
+            "dest_resc_hier" => Self::DestRescHierStrKw,
             "registerAsIntermediate" => Self::RegisterAsIntermediateKw,
             "staleAllIntermediateReplicas" => Self::StaleAllIntermediateReplicasKw,
             "sourceL1Desc" => Self::SourceL1DescKw,
@@ -361,6 +365,8 @@ impl From<&CondInputKw> for &'static str {
             CondInputKw::PurgeCacheKw => "purgeCache",
             CondInputKw::EmptyBundleOnlyKw => "emptyBundleOnly",
             CondInputKw::ReplicaTokenKw => "replicaToken",
+            CondInputKw::RescHierStrKw => "resc_hier",
+            CondInputKw::DestRescHierStrKw => "dest_resc_hier",
             CondInputKw::RegisterAsIntermediateKw => "registerAsIntermediate",
             CondInputKw::StaleAllIntermediateReplicasKw => "staleAllIntermediateReplicas",
             CondInputKw::SourceL1DescKw => "sourceL1Desc",
@@ -41,6 +41,9 @@ pub enum IcatColumn {
     CollectionComments = 507,
     CollectionCreateTime = 508,
     CollectionModifyTime = 509,
+    CollectionType = 510,
+    CollectionInfo1 = 511,
+    CollectionInfo2 = 512,
     MetadataAttributeName = 600,
     MetadataAttributeValue = 601,
     MetadataAttributeUnits = 602,
@@ -53,6 +56,12 @@ pub enum IcatColumn {
     MetadataCollectionAttributeId = 613,
     MetadataCollectionCreateTime = 614,
     MetadataCollectionModifyTime = 615,
+    MetadataResourceAttributeName = 630,
+    MetadataResourceAttributeValue = 631,
+    MetadataResourceAttributeUnits = 632,
+    MetadataResourceAttributeId = 633,
+    MetadataResourceCreateTime = 634,
+    MetadataResourceModifyTime = 635,
     MetaDataUserAttributeName = 640,
     MetaDataUserAttributeValue = 641,
     MetaDataUserAttributeUnits = 642,
@@ -69,8 +78,8 @@ pub enum IcatColumn {
     CollectionTokenNamespace = 712,
     CollectionAccessUserId = 713,
     CollectionAccessDataId = 714,
-    CollectionUserGroupId = 900,
-    CollectionUserGroupName = 901,
+    UserGroupId = 900,
+    UserGroupName = 901,
     ResourceId = 301,
     ResourceName = 302,
     ResourceZone = 303,
@@ -128,6 +137,15 @@ pub enum IcatColumn {
     ServerAddr = 1000009,
 }
 
+// The group columns are not specific to collections
+#[allow(non_upper_case_globals)]
+impl IcatColumn {
+    #[deprecated(note = "renamed to `IcatColumn::UserGroupId`")]
+    pub const CollectionUserGroupId: IcatColumn = IcatColumn::UserGroupId;
+    #[deprecated(note = "renamed to `IcatColumn::UserGroupName`")]
+    pub const CollectionUserGroupName: IcatColumn = IcatColumn::UserGroupName;
+}
+
 impl TryFrom<&str> for IcatColumn {
     type Error = IrodsError;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
@@ -171,6 +189,9 @@ impl TryFrom<&str> for IcatColumn {
             "507" => Ok(IcatColumn::CollectionComments),
             "508" => Ok(IcatColumn::CollectionCreateTime),
             "509" => Ok(IcatColumn::CollectionModifyTime),
+            "510" => Ok(IcatColumn::CollectionType),
+            "511" => Ok(IcatColumn::CollectionInfo1),
+            "512" => Ok(IcatColumn::CollectionInfo2),
             "600" => Ok(IcatColumn::MetadataAttributeName),
             "601" => Ok(IcatColumn::MetadataAttributeValue),
             "602" => Ok(IcatColumn::MetadataAttributeUnits),
@@ -183,6 +204,12 @@ impl TryFrom<&str> for IcatColumn {
             "613" => Ok(IcatColumn::MetadataCollectionAttributeId),
             "614" => Ok(IcatColumn::MetadataCollectionCreateTime),
             "615" => Ok(IcatColumn::MetadataCollectionModifyTime),
+            "630" => Ok(IcatColumn::MetadataResourceAttributeName),
+            "631" => Ok(IcatColumn::MetadataResourceAttributeValue),
+            "632" => Ok(IcatColumn::MetadataResourceAttributeUnits),
+            "633" => Ok(IcatColumn::MetadataResourceAttributeId),
+            "634" => Ok(IcatColumn::MetadataResourceCreateTime),
+            "635" => Ok(IcatColumn::MetadataResourceModifyTime),
             "640" => Ok(IcatColumn::MetaDataUserAttributeName),
             "641" => Ok(IcatColumn::MetaDataUserAttributeValue),
             "642" => Ok(IcatColumn::MetaDataUserAttributeUnits),
@@ -199,8 +226,8 @@ impl TryFrom<&str> for IcatColumn {
             "712" => Ok(IcatColumn::CollectionTokenNamespace),
             "713" => Ok(IcatColumn::CollectionAccessUserId),
             "714" => Ok(IcatColumn::CollectionAccessDataId),
-            "900" => Ok(IcatColumn::CollectionUserGroupId),
-            "901" => Ok(IcatColumn::CollectionUserGroupName),
+            "900" => Ok(IcatColumn::UserGroupId),
+            "901" => Ok(IcatColumn::UserGroupName),
             "301" => Ok(IcatColumn::ResourceId),
             "302" => Ok(IcatColumn::ResourceName),
             "303" => Ok(IcatColumn::ResourceZone),
@@ -292,6 +319,9 @@ impl TryFrom<&str> for IcatColumn {
             "507" => Ok(IcatColumn::CollectionComments),
             "508" => Ok(IcatColumn::CollectionCreateTime),
             "509" => Ok(IcatColumn::CollectionModifyTime),
+            "510" => Ok(IcatColumn::CollectionType),
+            "511" => Ok(IcatColumn::CollectionInfo1),
+            "512" => Ok(IcatColumn::CollectionInfo2),
             "600" => Ok(IcatColumn::MetadataAttributeName),
             "601" => Ok(IcatColumn::MetadataAttributeValue),
             "602" => Ok(IcatColumn::MetadataAttributeUnits),
@@ -304,6 +334,12 @@ impl TryFrom<&str> for IcatColumn {
             "613" => Ok(IcatColumn::MetadataCollectionAttributeId),
             "614" => Ok(IcatColumn::MetadataCollectionCreateTime),
             "615" => Ok(IcatColumn::MetadataCollectionModifyTime),
+            "630" => Ok(IcatColumn::MetadataResourceAttributeName),
+            "631" => Ok(IcatColumn::MetadataResourceAttributeValue),
+            "632" => Ok(IcatColumn::MetadataResourceAttributeUnits),
+            "633" => Ok(IcatColumn::MetadataResourceAttributeId),
+            "634" => Ok(IcatColumn::MetadataResourceCreateTime),
+            "635" => Ok(IcatColumn::MetadataResourceModifyTime),
             "640" => Ok(IcatColumn::MetaDataUserAttributeName),
             "641" => Ok(IcatColumn::MetaDataUserAttributeValue),
             "642" => Ok(IcatColumn::MetaDataUserAttributeUnits),
@@ -320,8 +356,8 @@ impl TryFrom<&str> for IcatColumn {
             "712" => Ok(IcatColumn::CollectionTokenNamespace),
             "713" => Ok(IcatColumn::CollectionAccessUserId),
             "714" => Ok(IcatColumn::CollectionAccessDataId),
-            "900" => Ok(IcatColumn::CollectionUserGroupId),
-            "901" => Ok(IcatColumn::CollectionUserGroupName),
+            "900" => Ok(IcatColumn::UserGroupId),
+            "901" => Ok(IcatColumn::UserGroupName),
             "301" => Ok(IcatColumn::ResourceId),
             "302" => Ok(IcatColumn::ResourceName),
             "303" => Ok(IcatColumn::ResourceZone),
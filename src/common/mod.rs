@@ -18,7 +18,7 @@ impl From<&IrodsProt> for &str {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum UserType {
     Group,
     User,
@@ -80,14 +80,26 @@ impl Into<&'static str> for AccessLevel {
     }
 }
 
+impl AccessLevel {
+    /// Orders the levels that grant access, from none to own.
+    pub fn rank(self) -> u8 {
+        match self {
+            AccessLevel::Own => 3,
+            AccessLevel::Write => 2,
+            AccessLevel::Read => 1,
+            AccessLevel::NoAccess | AccessLevel::Inherit | AccessLevel::NoInherit => 0,
+        }
+    }
+}
+
 impl TryFrom<&str> for AccessLevel {
     type Error = IrodsError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "own" => Ok(AccessLevel::Own),
-            "read object" => Ok(AccessLevel::Read),
-            "modify object" => Ok(AccessLevel::Write),
+            "read object" | "read_object" => Ok(AccessLevel::Read),
+            "modify object" | "modify_object" => Ok(AccessLevel::Write),
             "inherit" => Ok(AccessLevel::Inherit),
             "noinherit" => Ok(AccessLevel::NoInherit),
             "" => Ok(AccessLevel::NoAccess),
@@ -169,19 +181,48 @@ pub enum ObjectType {
 
 pub enum APN {
     Authentication = 110000,
+    DataObjRepl = 610,
     DataObjOpen = 602,
+    DataObjPut = 606,
+    DataObjGet = 608,
     DataObjUnlink = 615,
+    ModDataObjMeta = 622,
     DataObjClose = 673,
     DataObjLSeek = 674,
     DataObjRead = 675,
     DataObjWrite = 676,
+    DataObjPhymv = 631,
+    DataObjTrim = 632,
     ObjStat = 633,
     ExecMyRule = 625,
+    OprComplete = 626,
+    DataObjRename = 627,
+    DataObjTruncate = 652,
+    StructFileExtAndReg = 665,
     RmColl = 679,
+    ModColl = 680,
+    CollCreate = 681,
+    StructFileBundle = 666,
+    PhyPathReg = 630,
+    SyncMountedColl = 659,
+    GetHostForPut = 686,
+    GetHostForGet = 694,
+    DataObjCopy = 696,
     GenQuery = 702,
     GeneralAdmin = 701,
     ModAvu = 706,
     ModAccessControl = 707,
+    GenQuery2 = 10221,
+    GetFileDescriptorInfo = 20000,
+    AtomicApplyMetadataOperations = 20002,
+    ReplicaClose = 20004,
+    AtomicApplyAclOperations = 20005,
+    Touch = 20007,
+}
+
+pub mod error_code {
+    pub const USER_FILE_DOES_NOT_EXIST: i32 = -310000;
+    pub const CAT_NO_ROWS_FOUND: i32 = -808000;
 }
 
 pub mod response {
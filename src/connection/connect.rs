@@ -1,3 +1,5 @@
+use std::net::SocketAddr;
+
 use crate::error::errors::IrodsError;
 
 use crate::bosd::ProtocolEncoding;
@@ -16,4 +18,13 @@ where
     ) -> impl std::future::Future<
         Output = Result<UnauthenticatedConnection<T, Self::Transport>, IrodsError>,
     > + std::marker::Send;
+
+    /// The address of the server this connector connects to.
+    fn addr(&self) -> SocketAddr;
+
+    /// A connector with the same settings that connects to `host`, resolved
+    /// to `addr`, instead.
+    fn redirect(&self, host: &str, addr: SocketAddr) -> Self
+    where
+        Self: Sized;
 }
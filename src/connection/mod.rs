@@ -17,7 +17,7 @@ use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 
-use crate::common::APN;
+use crate::common::{response, APN};
 use crate::msg::bin_bytes_buf::BinBytesBuf;
 use crate::msg::header::{HandshakeHeader, SharedSecretHeader};
 use crate::msg::version::Version;
@@ -32,7 +32,7 @@ use crate::{
 };
 
 use self::authenticate::NativeAuthenticator;
-use self::ssl::SslConfig;
+use self::ssl::{PortalCipher, SslConfig};
 
 pub const MAX_PASSWORD_LEN: usize = 50;
 
@@ -67,6 +67,7 @@ where
     pub bytes_buf: Vec<u8>,
     pub error_buf: Vec<u8>,
     pub transport: S,
+    pub(crate) portal_cipher: Option<PortalCipher>,
 }
 
 impl<S> ResourceBundle<S>
@@ -80,6 +81,7 @@ where
             bytes_buf: Vec::new(),
             error_buf: Vec::new(),
             transport,
+            portal_cipher: None,
         }
     }
 }
@@ -109,25 +111,29 @@ where
         Ok(())
     }
 
-    pub(self) async fn send_shared_secret<T>(&mut self, size: usize) -> Result<(), IrodsError>
+    pub(self) async fn send_shared_secret<T>(
+        &mut self,
+        config: &SslConfig,
+    ) -> Result<(), IrodsError>
     where
         T: ProtocolEncoding,
     {
-        let shared_secret = &mut self.bytes_buf[..size];
-        rand::thread_rng().fill_bytes(shared_secret);
+        let mut shared_secret = vec![0; config.key_size];
+        rand::thread_rng().fill_bytes(&mut shared_secret);
 
-        let header = SharedSecretHeader { size };
+        let header = SharedSecretHeader {
+            size: config.key_size,
+        };
         let header_len = T::encode(&header, &mut self.header_buf)?;
 
         self.send_header_len(header_len).await?;
         self.send_from_header_buf(header_len).await?;
-        self.send_from_bytes_buf(size).await?;
+        self.transport.write_all(&shared_secret).await?;
 
-        Ok(())
-    }
+        // Portal transfers bypass TLS, so their payloads are encrypted with
+        // the shared secret instead
+        self.portal_cipher = Some(PortalCipher::new(&config.algorithm, shared_secret));
 
-    async fn send_from_bytes_buf(&mut self, len: usize) -> Result<(), IrodsError> {
-        self.transport.write_all(&mut self.bytes_buf[..len]).await?;
         Ok(())
     }
 
@@ -249,6 +255,102 @@ where
         Ok(self)
     }
 
+    /// Reads a reply that carries no message the caller cares about,
+    /// draining every buffer so the connection stays usable. Fails on a
+    /// negative status.
+    pub(crate) async fn read_status<T>(&mut self) -> Result<i32, IrodsError>
+    where
+        T: ProtocolEncoding,
+    {
+        let header = self.read_standard_header::<T>().await?;
+
+        self.read_to_msg_buf(header.msg_len).await?;
+        self.read_to_error_buf(header.error_len).await?;
+        self.read_to_bytes_buf(header.bs_len).await?;
+
+        if header.int_info < 0 {
+            return Err(IrodsError::Server(header.int_info));
+        }
+
+        Ok(header.int_info)
+    }
+
+    /// Like `read_status`, for operations on whole collections. While such an
+    /// operation runs, the server reports progress and waits for each report
+    /// to be acknowledged before it carries on.
+    pub(crate) async fn read_coll_opr_status<T>(&mut self) -> Result<i32, IrodsError>
+    where
+        T: ProtocolEncoding,
+    {
+        loop {
+            let header = self.read_standard_header::<T>().await?;
+
+            self.read_to_msg_buf(header.msg_len).await?;
+            self.read_to_error_buf(header.error_len).await?;
+            self.read_to_bytes_buf(header.bs_len).await?;
+
+            if header.int_info == response::SVR_TO_CLI_COLL_STAT {
+                self.transport
+                    .write_i32(response::SVR_TO_CLI_COLL_STAT_REPLY)
+                    .await?;
+                continue;
+            }
+
+            if header.int_info < 0 {
+                return Err(IrodsError::Server(header.int_info));
+            }
+
+            return Ok(header.int_info);
+        }
+    }
+
+    /// Reads a reply whose message matters even when the status is negative,
+    /// e.g., the error report of a JSON API. The status is not checked.
+    pub(crate) async fn read_status_and_msg<T, M>(&mut self) -> Result<(i32, Option<M>), IrodsError>
+    where
+        T: ProtocolEncoding,
+        M: Deserializable,
+    {
+        let header = self.read_standard_header::<T>().await?;
+
+        let msg = match header.msg_len {
+            0 => None,
+            len => Some(self.read_msg::<T, M>(len).await?),
+        };
+
+        self.read_to_error_buf(header.error_len).await?;
+        self.read_to_bytes_buf(header.bs_len).await?;
+
+        Ok((header.int_info, msg))
+    }
+
+    /// Reads a reply whose byte stream follows the message, e.g., the data of
+    /// a `DataObjGet`. The bytes are written to the start of `sink`.
+    pub(crate) async fn get_header_msg_and_bytes<T, M>(
+        &mut self,
+        sink: &mut Vec<u8>,
+    ) -> Result<(StandardHeader, M), IrodsError>
+    where
+        T: ProtocolEncoding,
+        M: Deserializable,
+    {
+        let header = self.read_standard_header::<T>().await?;
+
+        if header.int_info < 0 {
+            self.read_to_msg_buf(header.msg_len).await?;
+            self.read_to_error_buf(header.error_len).await?;
+            self.read_into_buf(sink, header.bs_len).await?;
+
+            return Err(IrodsError::Server(header.int_info));
+        }
+
+        let msg = self.read_msg::<T, M>(header.msg_len).await?;
+        self.read_to_error_buf(header.error_len).await?;
+        self.read_into_buf(sink, header.bs_len).await?;
+
+        Ok((header, msg))
+    }
+
     pub(crate) async fn get_header_and_msg<T, M>(
         &mut self,
     ) -> Result<(StandardHeader, M), IrodsError>
@@ -257,6 +359,16 @@ where
         M: Deserializable,
     {
         let header = self.read_standard_header::<T>().await?;
+
+        // Failures come without the expected message
+        if header.int_info < 0 {
+            self.read_to_msg_buf(header.msg_len).await?;
+            self.read_to_error_buf(header.error_len).await?;
+            self.read_to_bytes_buf(header.bs_len).await?;
+
+            return Err(IrodsError::Server(header.int_info));
+        }
+
         let msg = self.read_msg::<T, M>(header.msg_len as usize).await?;
 
         Ok((header, msg))
@@ -281,6 +393,30 @@ where
 
         Ok(())
     }
+
+    /// Like `send_header_then_msg`, but `bytes` ride along as the
+    /// message's byte stream.
+    pub(crate) async fn send_header_then_msg_and_bytes<T, M>(
+        &mut self,
+        msg: &M,
+        msg_type: MsgType,
+        int_info: i32,
+        bytes: &[u8],
+    ) -> Result<(), IrodsError>
+    where
+        T: ProtocolEncoding,
+        M: Serialiazable,
+    {
+        let msg_len = T::encode(msg, &mut self.msg_buf)?;
+
+        let header = StandardHeader::new(msg_type, msg_len, bytes.len(), 0, int_info);
+
+        self.send_standard_header::<T>(header).await?;
+        self.send_from_msg_buf(msg_len).await?;
+        self.transport.write_all(bytes).await?;
+
+        Ok(())
+    }
 }
 
 impl<T> UninitializedConnection<T, TcpStream>
@@ -305,6 +441,7 @@ where
                 bytes_buf: self.resources.bytes_buf,
                 error_buf: self.resources.error_buf,
                 transport: tls_stream,
+                portal_cipher: self.resources.portal_cipher,
             },
             account: self.account,
             phantom_protocol: PhantomData,
@@ -346,8 +483,11 @@ impl<T> UninitializedConnection<T, tokio_native_tls::TlsStream<TcpStream>>
 where
     T: ProtocolEncoding,
 {
-    pub(crate) async fn send_shared_secret(&mut self, size: usize) -> Result<(), IrodsError> {
-        self.resources.send_shared_secret::<T>(size).await?;
+    pub(crate) async fn send_shared_secret(
+        &mut self,
+        config: &SslConfig,
+    ) -> Result<(), IrodsError> {
+        self.resources.send_shared_secret::<T>(config).await?;
 
         Ok(())
     }
@@ -596,3 +736,90 @@ where
     pub(crate) version: (u8, u8, u8),
     pub(crate) phantom_protocol: PhantomData<T>,
 }
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use futures::TryStreamExt;
+    use tokio::io::{duplex, DuplexStream};
+
+    use crate::{
+        bosd::xml::XML,
+        common::{error_code, icat_column::IcatColumn},
+        msg::{gen_query::QueryBuilder, str_msg::StrMsg},
+    };
+
+    use super::*;
+
+    // A server reply as it comes off the wire
+    fn reply(int_info: i32, msg: &str) -> Vec<u8> {
+        let header = StandardHeader::new(MsgType::RodsApiReply, msg.len(), 0, 0, int_info);
+
+        let mut header_buf = Vec::new();
+        let header_len = XML::encode(&header, &mut header_buf).unwrap();
+
+        let mut out = (header_len as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(&header_buf[..header_len]);
+        out.extend_from_slice(msg.as_bytes());
+        out
+    }
+
+    // The other end must stay open for as long as the connection is used
+    async fn connection_with_replies(
+        replies: &[Vec<u8>],
+    ) -> (Connection<XML, DuplexStream>, DuplexStream) {
+        let (client, mut server) = duplex(1 << 16);
+
+        for reply in replies {
+            server.write_all(reply).await.unwrap();
+        }
+
+        let conn = Connection::new(
+            Account::test_account(),
+            ResourceBundle::new(client),
+            (4, 3, 2),
+            Vec::new(),
+        );
+
+        (conn, server)
+    }
+
+    #[tokio::test]
+    async fn failure_reply_is_server_error() {
+        let (mut conn, _server) = connection_with_replies(&[
+            reply(-826000, "<STR_PI><myStr>ignored</myStr></STR_PI>"),
+            reply(0, "<STR_PI><myStr>next</myStr></STR_PI>"),
+        ])
+        .await;
+
+        let err = conn.get_header_and_msg::<StrMsg>().await.unwrap_err();
+        assert!(matches!(err, IrodsError::Server(-826000)));
+
+        // The failed reply was read in full, so the next one parses
+        let (_, msg) = conn.get_header_and_msg::<StrMsg>().await.unwrap();
+        assert_eq!(msg.my_str, "next");
+    }
+
+    #[tokio::test]
+    async fn missing_path_stats_as_none() {
+        let (mut conn, _server) =
+            connection_with_replies(&[reply(error_code::USER_FILE_DOES_NOT_EXIST, "")]).await;
+
+        let stat = conn.try_stat(Path::new("/tempZone/missing")).await.unwrap();
+        assert!(stat.is_none());
+    }
+
+    #[tokio::test]
+    async fn query_without_matches_is_empty() {
+        let (mut conn, _server) =
+            connection_with_replies(&[reply(error_code::CAT_NO_ROWS_FOUND, "")]).await;
+
+        let mut inp = QueryBuilder::new()
+            .select(IcatColumn::CollectionName)
+            .build();
+
+        let rows: Vec<_> = conn.query(&mut inp).await.try_collect().await.unwrap();
+        assert!(rows.is_empty());
+    }
+}
@@ -46,6 +46,26 @@ where
             phantom: std::marker::PhantomData,
         }
     }
+
+    /// Opens a connection to `host` outside of the pool, on the same port
+    /// and with the same account, authenticator and TLS settings as the
+    /// pooled connections. Used to talk to a resource server directly.
+    pub async fn connect_to_host(
+        &self,
+        host: &str,
+    ) -> Result<Connection<T, C::Transport>, IrodsError> {
+        let port = self.connector.addr().port();
+
+        let addr = tokio::net::lookup_host((host, port))
+            .await?
+            .next()
+            .ok_or_else(|| IrodsError::Other(format!("Failed to resolve host [{host}]")))?;
+
+        let connector = self.connector.redirect(host, addr);
+        let unauth_conn = connector.connect(self.account.clone()).await?;
+
+        self.authenticator.authenticate(unauth_conn).await
+    }
 }
 
 impl<T, C, A> Manager for IrodsManager<T, C, A>
@@ -7,8 +7,10 @@ use std::{
 };
 
 use crate::error::errors::IrodsError;
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
 use futures::TryFutureExt;
 use native_tls::TlsConnector;
+use rand::RngCore;
 use tokio::net::TcpStream as AsyncTcpStream;
 use tokio::{
     fs::File,
@@ -27,6 +29,7 @@ pub struct SslConnector {
     inner: Arc<SslConnectorInner>,
 }
 
+#[derive(Clone)]
 pub struct SslConfig {
     pub cert_file: PathBuf,
     pub domain: String,
@@ -133,8 +136,124 @@ where
             .into_tls(blocking_connector, &self.inner.config.domain)
             .await?;
         conn.send_handshake_header(&self.inner.config).await?;
-        conn.send_shared_secret(self.inner.config.key_size).await?;
+        conn.send_shared_secret(&self.inner.config).await?;
 
         Ok(conn.into_unauthenticated(version))
     }
+
+    fn addr(&self) -> SocketAddr {
+        self.inner.addr
+    }
+
+    // The certificate presented by the other server is checked against
+    // its own host name
+    fn redirect(&self, host: &str, addr: SocketAddr) -> Self {
+        let mut config = self.inner.config.clone();
+        config.domain = host.to_string();
+
+        Self::new(addr, config)
+    }
+}
+
+/// Encrypts the payloads of portal transfers on an SSL connection, which go
+/// over plain sockets. Each buffer is sent as a random IV, `key.len()` bytes
+/// long, followed by the ciphertext, as `putFileToPortal` and
+/// `getFileFromPortal` in the server's `lib/core/src/rcPortalOpr.cpp` do.
+///
+/// The shared secret is used as the key as is. The negotiated `salt_size`
+/// and `hash_rounds` go to the server in the handshake header, but its
+/// `irods::buffer_crypt` only stores them: its `encrypt` and `decrypt` hand
+/// the shared secret straight to OpenSSL, so no key is derived from them.
+#[derive(Clone)]
+pub(crate) struct PortalCipher {
+    algorithm: String,
+    key: Vec<u8>,
+}
+
+macro_rules! cbc_encrypt {
+    ($aes:ty, $key:expr, $iv:expr, $plain:expr) => {
+        cbc::Encryptor::<$aes>::new_from_slices($key, $iv)
+            .map_err(|_| IrodsError::Other("Invalid portal key or IV length".into()))?
+            .encrypt_padded_vec_mut::<Pkcs7>($plain)
+    };
+}
+
+macro_rules! cbc_decrypt {
+    ($aes:ty, $key:expr, $iv:expr, $cipher:expr) => {
+        cbc::Decryptor::<$aes>::new_from_slices($key, $iv)
+            .map_err(|_| IrodsError::Other("Invalid portal key or IV length".into()))?
+            .decrypt_padded_vec_mut::<Pkcs7>($cipher)
+            .map_err(|_| IrodsError::Other("Failed to decrypt portal buffer".into()))?
+    };
+}
+
+impl PortalCipher {
+    pub(crate) fn new(algorithm: &str, key: Vec<u8>) -> Self {
+        Self {
+            algorithm: algorithm.to_uppercase(),
+            key,
+        }
+    }
+
+    pub(crate) fn encrypt(&self, plain: &[u8]) -> Result<Vec<u8>, IrodsError> {
+        // The server makes the IV as long as the key and passes it to
+        // OpenSSL, whose CBC mode only reads the first block of it
+        let mut iv = vec![0; self.key.len()];
+        rand::thread_rng().fill_bytes(&mut iv);
+
+        let block_iv = &iv[..16.min(iv.len())];
+
+        let cipher = match self.algorithm.as_str() {
+            "AES-128-CBC" => cbc_encrypt!(aes::Aes128, &self.key, block_iv, plain),
+            "AES-192-CBC" => cbc_encrypt!(aes::Aes192, &self.key, block_iv, plain),
+            "AES-256-CBC" => cbc_encrypt!(aes::Aes256, &self.key, block_iv, plain),
+            other => {
+                return Err(IrodsError::Other(format!(
+                    "Unsupported portal encryption algorithm [{other}]"
+                )))
+            }
+        };
+
+        iv.extend_from_slice(&cipher);
+
+        Ok(iv)
+    }
+
+    pub(crate) fn decrypt(&self, payload: &[u8]) -> Result<Vec<u8>, IrodsError> {
+        if payload.len() < self.key.len() {
+            return Err(IrodsError::Other(
+                "Portal buffer shorter than its IV".into(),
+            ));
+        }
+
+        let (iv, cipher) = payload.split_at(self.key.len());
+        let block_iv = &iv[..16.min(iv.len())];
+
+        Ok(match self.algorithm.as_str() {
+            "AES-128-CBC" => cbc_decrypt!(aes::Aes128, &self.key, block_iv, cipher),
+            "AES-192-CBC" => cbc_decrypt!(aes::Aes192, &self.key, block_iv, cipher),
+            "AES-256-CBC" => cbc_decrypt!(aes::Aes256, &self.key, block_iv, cipher),
+            other => {
+                return Err(IrodsError::Other(format!(
+                    "Unsupported portal encryption algorithm [{other}]"
+                )))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn portal_cipher_round_trip() {
+        let cipher = PortalCipher::new("AES-256-CBC", vec![7; 32]);
+        let plain = b"the quick brown fox jumps over the lazy dog".to_vec();
+
+        let payload = cipher.encrypt(&plain).unwrap();
+
+        assert_eq!(payload.len() % 16, 0);
+        assert_eq!(cipher.decrypt(&payload).unwrap(), plain);
+    }
 }
@@ -49,4 +49,12 @@ where
 
         Ok(conn.into_unauthenticated(version))
     }
+
+    fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn redirect(&self, _host: &str, addr: SocketAddr) -> Self {
+        Self { addr }
+    }
 }
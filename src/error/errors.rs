@@ -1,6 +1,6 @@
 use base64::DecodeError;
 use native_tls::HandshakeError;
-use std::{convert::Infallible, net::TcpStream, num::ParseIntError, str::Utf8Error};
+use std::{convert::Infallible, net::TcpStream, num::ParseIntError, path::PathBuf, str::Utf8Error};
 
 use thiserror::Error;
 
@@ -70,9 +70,37 @@ pub enum IrodsError {
     #[error("unexpected response from server, expected: [{0}]")]
     UnexpectedResponse(String),
 
+    #[error("server returned error code [{0}]")]
+    Server(i32),
+
     #[error("Error: [{0}]")]
     Other(String),
 
+    #[error("{} transfer(s) failed", .0.len())]
+    TransferFailed(Vec<(PathBuf, IrodsError)>),
+
+    #[error("transfer cancelled")]
+    Cancelled,
+
+    #[error("operation [{index}] of batch failed with error code [{code}]: [{message}]")]
+    BatchFailed {
+        code: i32,
+        index: usize,
+        message: String,
+    },
+
+    #[error(
+        "{} of {} slice(s) of [{}] failed after retries",
+        .failures.len(),
+        .num_slices,
+        .path.display()
+    )]
+    SlicesFailed {
+        path: PathBuf,
+        num_slices: usize,
+        failures: Vec<(usize, IrodsError)>,
+    },
+
     #[error("parse int error")]
     ParseInt {
         #[from]
@@ -1,11 +1,11 @@
 use std::path::Path;
 
 use async_stream::try_stream;
-use futures::{future::BoxFuture, Stream};
+use futures::{pin_mut, Stream, StreamExt};
 
 use crate::{
     bosd::ProtocolEncoding,
-    common::{icat_column::IcatColumn, ObjectType},
+    common::{icat_column::IcatColumn, AccessLevel, ObjectType, UserType},
     connection::Connection,
     error::errors::IrodsError,
     msg::gen_query::{IcatPredicate, QueryBuilder},
@@ -24,6 +24,12 @@ where
     where
         'p: 'this,
     {
+        let parent = path.parent().and_then(Path::to_str).unwrap_or_default();
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
         let mut inp = QueryBuilder::new()
             .select(IcatColumn::DataObjectAccessName)
             .select(IcatColumn::UserName)
@@ -31,7 +37,11 @@ where
             .select(IcatColumn::UserType)
             .condition(
                 IcatColumn::CollectionName,
-                IcatPredicate::Equals(path.to_str().unwrap().to_string()),
+                IcatPredicate::Equals(parent.to_string()),
+            )
+            .condition(
+                IcatColumn::DataObjectBaseName,
+                IcatPredicate::Equals(name.to_string()),
             )
             .build();
 
@@ -68,4 +78,120 @@ where
             }
         }
     }
+
+    /// The ACLs of a data object or collection, as stored.
+    pub async fn list_acls(&mut self, path: &Path) -> Result<Vec<AccessControl>, IrodsError> {
+        let stat = self.stat(path).await?;
+        let mut acls = Vec::new();
+
+        match stat.object_type {
+            ObjectType::DataObj => {
+                let rows = self.list_acls_for_data_object(path).await;
+                pin_mut!(rows);
+
+                while let Some(acl) = rows.next().await {
+                    acls.push(acl?);
+                }
+            }
+            ObjectType::Coll => {
+                let rows = self.list_acls_for_collection(path).await;
+                pin_mut!(rows);
+
+                while let Some(acl) = rows.next().await {
+                    acls.push(acl?);
+                }
+            }
+            _ => return Err(IrodsError::Other("No such path".to_string())),
+        }
+
+        Ok(acls)
+    }
+
+    /// Like `list_acls`, but every ACL of a group is followed by one for each
+    /// of its members, with `via_group` set.
+    pub async fn list_acls_with_group_members(
+        &mut self,
+        path: &Path,
+    ) -> Result<Vec<AccessControl>, IrodsError> {
+        let mut expanded = Vec::new();
+
+        for acl in self.list_acls(path).await? {
+            let members = match acl.user_type {
+                UserType::Group => self.list_users_in_group(acl.user_name.clone()).await?,
+                _ => Vec::new(),
+            };
+
+            let granted = acl.clone();
+            expanded.push(acl);
+
+            for member in members {
+                expanded.push(AccessControl {
+                    path: granted.path.clone(),
+                    user_name: member.name,
+                    user_zone: member.zone,
+                    user_type: member.user_type,
+                    access_type: granted.access_type,
+                    via_group: Some(granted.user_name.clone()),
+                });
+            }
+        }
+
+        Ok(expanded)
+    }
+
+    /// The highest access `user` of `zone` has to `path`, whether granted
+    /// directly or to one of its groups.
+    pub async fn effective_access(
+        &mut self,
+        path: &Path,
+        user: &str,
+        zone: &str,
+    ) -> Result<AccessLevel, IrodsError> {
+        let groups = self.list_groups_of_user(user, zone).await?;
+
+        let effective = self
+            .list_acls(path)
+            .await?
+            .into_iter()
+            .filter(|acl| {
+                (acl.user_name == user && acl.user_zone == zone)
+                    || (acl.user_type == UserType::Group && groups.contains(&acl.user_name))
+            })
+            .map(|acl| acl.access_type)
+            .max_by_key(|access| access.rank())
+            .unwrap_or(AccessLevel::NoAccess);
+
+        Ok(effective)
+    }
+
+    async fn list_groups_of_user(
+        &mut self,
+        user: &str,
+        zone: &str,
+    ) -> Result<Vec<String>, IrodsError> {
+        let mut inp = QueryBuilder::new()
+            .select(IcatColumn::UserGroupName)
+            .condition(
+                IcatColumn::UserName,
+                IcatPredicate::Equals(user.to_string()),
+            )
+            .condition(
+                IcatColumn::UserZone,
+                IcatPredicate::Equals(zone.to_string()),
+            )
+            .build();
+
+        let mut groups = Vec::new();
+
+        let rows = self.query(&mut inp).await;
+        pin_mut!(rows);
+
+        while let Some(row) = rows.next().await {
+            if let Some(group) = row?.take(IcatColumn::UserGroupName) {
+                groups.push(group);
+            }
+        }
+
+        Ok(groups)
+    }
 }
@@ -1,29 +1,134 @@
 use std::path::Path;
 
+use futures::{pin_mut, StreamExt};
+
 use crate::{
     bosd::ProtocolEncoding,
-    common::{AccessLevel, ObjectType, UserType, APN},
+    common::{icat_column::IcatColumn, AccessLevel, ObjectType, UserType, APN},
     connection::{Connection, MAX_PASSWORD_LEN},
     error::errors::IrodsError,
-    msg::{acls::ModifyAccessRequest, admin::GeneralAdminInpBuilder, header::MsgType},
-    AdminOperation, AdminTarget,
+    msg::{
+        acls::ModifyAccessRequest,
+        admin::GeneralAdminInpBuilder,
+        gen_query::{IcatPredicate, QueryBuilder},
+        header::MsgType,
+    },
+    AdminOperation, AdminTarget, GroupMember,
 };
 
+struct Grant {
+    name: String,
+    zone: String,
+    access_level: AccessLevel,
+}
+
+pub struct ChmodRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    path: &'p Path,
+    grants: Vec<Grant>,
+    recursive: bool,
+    admin: bool,
+}
+
+impl<'conn, 'p, T, C> ChmodRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, path: &'p Path) -> Self {
+        Self {
+            conn,
+            path,
+            grants: Vec::new(),
+            recursive: false,
+            admin: false,
+        }
+    }
+
+    /// Gives a user or group of the local zone `access_level`, which
+    /// replaces any access it had. `AccessLevel::NoAccess` revokes it.
+    pub fn grant(self, name: &str, access_level: AccessLevel) -> Self {
+        self.grant_in_zone(name, "", access_level)
+    }
+
+    /// Like `grant`, for a user or group of another zone.
+    pub fn grant_in_zone(mut self, name: &str, zone: &str, access_level: AccessLevel) -> Self {
+        self.grants.push(Grant {
+            name: name.to_owned(),
+            zone: zone.to_owned(),
+            access_level,
+        });
+        self
+    }
+
+    /// Apply the grants to everything under a collection as well.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub fn admin(mut self, admin: bool) -> Self {
+        self.admin = admin;
+        self
+    }
+
+    /// Sends the grants in order, one request each, and stops at the first
+    /// one that fails. This is not atomic: grants sent before the failure
+    /// stay applied. Use `atomic_acl_operations` for an all-or-nothing
+    /// change to a single object.
+    pub async fn execute(self) -> Result<(), IrodsError> {
+        if self.grants.iter().any(|grant| {
+            matches!(
+                grant.access_level,
+                AccessLevel::Inherit | AccessLevel::NoInherit
+            )
+        }) {
+            return Err(IrodsError::Other(
+                "Use set_access_inherit to change inheritance".into(),
+            ));
+        }
+
+        let stat = self.conn.stat(self.path).await?;
+        let recursive = match stat.object_type {
+            ObjectType::Coll => self.recursive,
+            ObjectType::DataObj => false,
+            _ => return Err(IrodsError::Other("No such path".to_string())),
+        };
+
+        for grant in self.grants {
+            let inp = ModifyAccessRequest::new(
+                recursive,
+                grant.access_level,
+                grant.name,
+                grant.zone,
+                self.path.to_path_buf(),
+            )
+            .admin(self.admin);
+
+            self.conn
+                .send_header_then_msg(&inp, MsgType::RodsApiReq, APN::ModAccessControl as i32)
+                .await?;
+
+            self.conn.resources.read_status::<T>().await?;
+        }
+
+        Ok(())
+    }
+}
+
 impl<T, C> Connection<T, C>
 where
     T: ProtocolEncoding,
     C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
 {
-    /// Stat the path, call appropriate method
-    pub async fn chmod(
-        &mut self,
-        path: &Path,
-        access_level: AccessLevel,
-        user: String,
-        zone: String,
-        admin: bool,
-    ) -> Result<(), IrodsError> {
-        todo!()
+    /// Changes who may access a data object or collection. Grants to users
+    /// and groups alike are added to the returned request.
+    pub fn chmod<'conn, 'p>(&'conn mut self, path: &'p Path) -> ChmodRequest<'conn, 'p, T, C> {
+        ChmodRequest::new(self, path)
     }
 
     /// Should only be called on a collection
@@ -203,8 +308,51 @@ where
     }
 
     /// GetGroup in go-irodsclient
-    pub async fn list_users_in_group(&mut self, group: String) -> Result<Vec<String>, IrodsError> {
-        todo!()
+    pub async fn list_users_in_group(
+        &mut self,
+        group: String,
+    ) -> Result<Vec<GroupMember>, IrodsError> {
+        let mut inp = QueryBuilder::new()
+            .select(IcatColumn::UserName)
+            .select(IcatColumn::UserZone)
+            .select(IcatColumn::UserType)
+            .condition(
+                IcatColumn::UserGroupName,
+                IcatPredicate::Equals(group.clone()),
+            )
+            .build();
+
+        let mut members = Vec::new();
+
+        let rows = self.query(&mut inp).await;
+        pin_mut!(rows);
+
+        while let Some(row) = rows.next().await {
+            let mut row = row?;
+
+            let name = row
+                .take(IcatColumn::UserName)
+                .ok_or_else(|| IrodsError::Other("Missing user_name".to_owned()))?;
+
+            // A group is listed as a member of itself
+            if name == group {
+                continue;
+            }
+
+            members.push(GroupMember {
+                name,
+                zone: row
+                    .take(IcatColumn::UserZone)
+                    .ok_or_else(|| IrodsError::Other("Missing user_zone".to_owned()))?,
+                user_type: row
+                    .take(IcatColumn::UserType)
+                    .ok_or_else(|| IrodsError::Other("Missing user_type".to_owned()))?
+                    .as_str()
+                    .try_into()?,
+            });
+        }
+
+        Ok(members)
     }
 
     pub async fn list_groups(&mut self) -> Result<Vec<String>, IrodsError> {
@@ -0,0 +1,251 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{AccessLevel, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{bytes_buf::BytesBuf, header::MsgType},
+    AVUTarget,
+};
+
+#[derive(Serialize)]
+struct AclOperation {
+    entity_name: String,
+    acl: &'static str,
+}
+
+#[derive(Serialize)]
+struct AclOperations<'p> {
+    logical_path: &'p str,
+    admin_mode: bool,
+    operations: Vec<AclOperation>,
+}
+
+/// ACL changes to one data object or collection that the server applies
+/// all together or not at all.
+pub struct AtomicAclRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    path: &'p Path,
+    admin: bool,
+    operations: Vec<(String, AccessLevel)>,
+}
+
+impl<'conn, 'p, T, C> AtomicAclRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, path: &'p Path) -> Self {
+        Self {
+            conn,
+            path,
+            admin: false,
+            operations: Vec::new(),
+        }
+    }
+
+    /// Gives a user or group `access_level`. `AccessLevel::NoAccess`
+    /// revokes its access.
+    pub fn set(mut self, name: &str, access_level: AccessLevel) -> Self {
+        self.operations.push((name.to_owned(), access_level));
+        self
+    }
+
+    pub fn admin(mut self, admin: bool) -> Self {
+        self.admin = admin;
+        self
+    }
+
+    pub async fn execute(self) -> Result<(), IrodsError> {
+        let operations = self
+            .operations
+            .into_iter()
+            .map(|(entity_name, access_level)| {
+                let acl = match access_level {
+                    AccessLevel::Own => "own",
+                    AccessLevel::Write => "write",
+                    AccessLevel::Read => "read",
+                    AccessLevel::NoAccess => "null",
+                    AccessLevel::Inherit | AccessLevel::NoInherit => {
+                        return Err(IrodsError::Other(
+                            "Use set_access_inherit to change inheritance".into(),
+                        ))
+                    }
+                };
+
+                Ok(AclOperation { entity_name, acl })
+            })
+            .collect::<Result<Vec<_>, IrodsError>>()?;
+
+        let inp = AclOperations {
+            logical_path: self.path.to_str().unwrap(),
+            admin_mode: self.admin,
+            operations,
+        };
+
+        self.conn
+            .apply_atomically(APN::AtomicApplyAclOperations, &serde_json::to_string(&inp)?)
+            .await
+    }
+}
+
+#[derive(Serialize)]
+struct MetadataOperation {
+    operation: &'static str,
+    attribute: String,
+    value: String,
+    units: String,
+}
+
+#[derive(Serialize)]
+struct MetadataOperations<'p> {
+    entity_name: &'p str,
+    entity_type: &'static str,
+    admin_mode: bool,
+    operations: Vec<MetadataOperation>,
+}
+
+/// AVU changes to one entity that the server applies all together or not at
+/// all.
+pub struct AtomicMetadataRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    target_type: AVUTarget,
+    target_name: &'p str,
+    admin: bool,
+    operations: Vec<MetadataOperation>,
+}
+
+impl<'conn, 'p, T, C> AtomicMetadataRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(
+        conn: &'conn mut Connection<T, C>,
+        target_type: AVUTarget,
+        target_name: &'p str,
+    ) -> Self {
+        Self {
+            conn,
+            target_type,
+            target_name,
+            admin: false,
+            operations: Vec::new(),
+        }
+    }
+
+    pub fn add(self, attribute: &str, value: &str, units: &str) -> Self {
+        self.push("add", attribute, value, units)
+    }
+
+    pub fn remove(self, attribute: &str, value: &str, units: &str) -> Self {
+        self.push("remove", attribute, value, units)
+    }
+
+    fn push(mut self, operation: &'static str, attribute: &str, value: &str, units: &str) -> Self {
+        self.operations.push(MetadataOperation {
+            operation,
+            attribute: attribute.to_owned(),
+            value: value.to_owned(),
+            units: units.to_owned(),
+        });
+        self
+    }
+
+    pub fn admin(mut self, admin: bool) -> Self {
+        self.admin = admin;
+        self
+    }
+
+    pub async fn execute(self) -> Result<(), IrodsError> {
+        let entity_type = match self.target_type {
+            AVUTarget::DataObject => "data_object",
+            AVUTarget::Collection => "collection",
+            AVUTarget::User => "user",
+            AVUTarget::Resource => "resource",
+        };
+
+        let inp = MetadataOperations {
+            entity_name: self.target_name,
+            entity_type,
+            admin_mode: self.admin,
+            operations: self.operations,
+        };
+
+        self.conn
+            .apply_atomically(
+                APN::AtomicApplyMetadataOperations,
+                &serde_json::to_string(&inp)?,
+            )
+            .await
+    }
+}
+
+/// What the server reports about the operation that made a batch fail.
+#[derive(Deserialize)]
+struct FailedOperation {
+    #[serde(default)]
+    operation_index: usize,
+    #[serde(default)]
+    error_message: String,
+}
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Changes several ACLs of a data object or collection in one
+    /// transaction.
+    pub fn atomic_acl_operations<'conn, 'p>(
+        &'conn mut self,
+        path: &'p Path,
+    ) -> AtomicAclRequest<'conn, 'p, T, C> {
+        AtomicAclRequest::new(self, path)
+    }
+
+    /// Adds and removes several AVUs of an entity in one transaction.
+    /// `target_name` is a path for data objects and collections, a name
+    /// otherwise.
+    pub fn atomic_metadata_operations<'conn, 'p>(
+        &'conn mut self,
+        target_type: AVUTarget,
+        target_name: &'p str,
+    ) -> AtomicMetadataRequest<'conn, 'p, T, C> {
+        AtomicMetadataRequest::new(self, target_type, target_name)
+    }
+
+    async fn apply_atomically(&mut self, api: APN, json: &str) -> Result<(), IrodsError> {
+        let inp = BytesBuf::new(json);
+
+        self.resources
+            .send_header_then_msg::<T, _>(&inp, MsgType::RodsApiReq, api as i32)
+            .await?;
+
+        let (status, out) = self.resources.read_status_and_msg::<T, BytesBuf>().await?;
+
+        if status >= 0 {
+            return Ok(());
+        }
+
+        match out.and_then(|out| serde_json::from_str::<FailedOperation>(&out.buf).ok()) {
+            Some(failed) => Err(IrodsError::BatchFailed {
+                code: status,
+                index: failed.operation_index,
+                message: failed.error_message,
+            }),
+            None => Err(IrodsError::Server(status)),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::Path;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{header::MsgType, struct_file_ext_and_reg_inp::StructFileExtAndRegInp},
+    DataObjectType,
+};
+
+#[derive(Debug, Clone, Copy)]
+struct BundleOptions<'p> {
+    format: DataObjectType,
+    resc: Option<&'p str>,
+    force: bool,
+    bulk_register: bool,
+}
+
+impl Default for BundleOptions<'_> {
+    fn default() -> Self {
+        Self {
+            format: DataObjectType::Tar,
+            resc: None,
+            force: false,
+            bulk_register: false,
+        }
+    }
+}
+
+/// Packs a collection into a structured file or unpacks one into a
+/// collection, depending on the API it is sent to.
+pub struct BundleRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    api: APN,
+    archive: &'p Path,
+    collection: &'p Path,
+    opts: BundleOptions<'p>,
+}
+
+impl<'conn, 'p, T, C> BundleRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    fn new(
+        conn: &'conn mut Connection<T, C>,
+        api: APN,
+        archive: &'p Path,
+        collection: &'p Path,
+    ) -> Self {
+        Self {
+            conn,
+            api,
+            archive,
+            collection,
+            opts: BundleOptions::default(),
+        }
+    }
+
+    /// The archive format, tar by default. Only `Tar`, `GzipTar`, `Bzip2`
+    /// and `Zip` are structured files.
+    pub fn format(mut self, format: DataObjectType) -> Self {
+        self.opts.format = format;
+        self
+    }
+
+    /// The resource to create the archive or the extracted data objects on.
+    pub fn set_resc(mut self, resc: &'p str) -> Self {
+        self.opts.resc = Some(resc);
+        self
+    }
+
+    /// Overwrite an existing archive, or existing data objects on extraction.
+    pub fn force(mut self, force: bool) -> Self {
+        self.opts.force = force;
+        self
+    }
+
+    /// Register extracted data objects in bulk rather than one at a time.
+    /// Has no effect when bundling.
+    pub fn bulk_register(mut self, bulk_register: bool) -> Self {
+        self.opts.bulk_register = bulk_register;
+        self
+    }
+
+    fn make_inp(&self) -> Result<StructFileExtAndRegInp, IrodsError> {
+        let mut inp = StructFileExtAndRegInp::new(
+            self.archive.to_str().unwrap().to_owned(),
+            self.collection.to_str().unwrap().to_owned(),
+        );
+
+        if !matches!(
+            self.opts.format,
+            DataObjectType::Tar
+                | DataObjectType::GzipTar
+                | DataObjectType::Bzip2
+                | DataObjectType::Zip
+        ) {
+            return Err(IrodsError::Other(
+                "Format is not a structured file type".into(),
+            ));
+        }
+
+        let format: &str = self.opts.format.into();
+        inp.cond_input
+            .add_kw(CondInputKw::DataTypeKw, format.to_string());
+
+        if let Some(r) = self.opts.resc {
+            inp.cond_input
+                .add_kw(CondInputKw::DestRescNameKw, r.to_string());
+        }
+
+        if self.opts.force {
+            inp.cond_input.set_kw(CondInputKw::ForceFlagKw);
+        }
+
+        if self.opts.bulk_register && matches!(self.api, APN::StructFileExtAndReg) {
+            inp.cond_input.set_kw(CondInputKw::BulkOprKw);
+        }
+
+        Ok(inp)
+    }
+
+    pub async fn execute(self) -> Result<(), IrodsError> {
+        let inp = self.make_inp()?;
+
+        self.conn
+            .resources
+            .send_header_then_msg::<T, _>(&inp, MsgType::RodsApiReq, self.api as i32)
+            .await?;
+
+        self.conn.resources.read_coll_opr_status::<T>().await?;
+
+        Ok(())
+    }
+}
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Packs `collection` into a new archive data object at `archive`.
+    pub fn bundle<'conn, 'p>(
+        &'conn mut self,
+        collection: &'p Path,
+        archive: &'p Path,
+    ) -> BundleRequest<'conn, 'p, T, C> {
+        BundleRequest::new(self, APN::StructFileBundle, archive, collection)
+    }
+
+    /// Unpacks the archive data object at `archive` into `collection`,
+    /// registering its contents as data objects.
+    pub fn extract<'conn, 'p>(
+        &'conn mut self,
+        archive: &'p Path,
+        collection: &'p Path,
+    ) -> BundleRequest<'conn, 'p, T, C> {
+        BundleRequest::new(self, APN::StructFileExtAndReg, archive, collection)
+    }
+}
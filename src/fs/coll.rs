@@ -0,0 +1,60 @@
+use std::path::Path;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, ObjectType, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{coll::CollInp, header::MsgType},
+};
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    fn make_create_coll_inp(path: &Path, parents: bool) -> CollInp {
+        let mut inp = CollInp::builder().build();
+
+        inp.name = path.to_str().unwrap().to_owned();
+
+        if parents {
+            inp.cond_input.set_kw(CondInputKw::RecursiveOprKw);
+        }
+
+        inp
+    }
+
+    /// With `parents` set, missing parent collections are created as well
+    /// and an existing collection is not an error, i.e., `mkdir -p`.
+    pub async fn create_collection(
+        &mut self,
+        path: &Path,
+        parents: bool,
+    ) -> Result<(), IrodsError> {
+        let inp = Self::make_create_coll_inp(path, parents);
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::CollCreate as i32)
+            .await?;
+
+        self.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+
+    /// Makes sure a collection exists at `path`, creating it and any missing
+    /// parents if needed. Succeeds if it is already there, which makes it
+    /// safe to call from several transfers at once.
+    pub async fn ensure_collection(&mut self, path: &Path) -> Result<(), IrodsError> {
+        let err = match self.create_collection(path, true).await {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+
+        // Someone else may have created it first
+        match self.try_stat(path).await? {
+            Some(stat) if matches!(stat.object_type, ObjectType::Coll) => Ok(()),
+            _ => Err(err),
+        }
+    }
+}
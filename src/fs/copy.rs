@@ -0,0 +1,181 @@
+use std::{collections::VecDeque, path::Path};
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, ObjectType, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{data_obj_copy_inp::DataObjCopyInp, data_obj_inp::DataObjInp, header::MsgType},
+};
+
+use super::OprType;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct CopyOptions<'p> {
+    resc: Option<&'p str>,
+    force: bool,
+    checksum: bool,
+    verify_checksum: bool,
+}
+
+pub struct CopyRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    src: &'p Path,
+    dst: &'p Path,
+    opts: CopyOptions<'p>,
+    recursive: bool,
+}
+
+impl<'conn, 'p, T, C> CopyRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, src: &'p Path, dst: &'p Path) -> Self {
+        Self {
+            conn,
+            src,
+            dst,
+            opts: CopyOptions::default(),
+            recursive: false,
+        }
+    }
+
+    pub fn set_resc(mut self, resc: &'p str) -> Self {
+        self.opts.resc = Some(resc);
+        self
+    }
+
+    pub fn force(mut self, force: bool) -> Self {
+        self.opts.force = force;
+        self
+    }
+
+    /// Have the server register a checksum for the copy.
+    pub fn checksum(mut self, checksum: bool) -> Self {
+        self.opts.checksum = checksum;
+        self
+    }
+
+    /// Have the server verify the copy against the source's checksum.
+    pub fn verify_checksum(mut self, verify_checksum: bool) -> Self {
+        self.opts.verify_checksum = verify_checksum;
+        self
+    }
+
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub async fn execute(self) -> Result<(), IrodsError> {
+        let stat = self.conn.stat(self.src).await?;
+
+        match stat.object_type {
+            ObjectType::DataObj => self.conn.copy_data_obj(self.src, self.dst, self.opts).await,
+            ObjectType::Coll if self.recursive => {
+                self.conn.copy_coll(self.src, self.dst, self.opts).await
+            }
+            ObjectType::Coll => Err(IrodsError::Other(
+                "Path is a collection and recursive flag not set".into(),
+            )),
+            _ => Err(IrodsError::Other("No such path".to_string())),
+        }
+    }
+}
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Copies a data object, or with `recursive` a collection, on the server
+    /// without the data passing through the client.
+    pub fn copy<'conn, 'p>(
+        &'conn mut self,
+        src: &'p Path,
+        dst: &'p Path,
+    ) -> CopyRequest<'conn, 'p, T, C> {
+        CopyRequest::new(self, src, dst)
+    }
+
+    fn make_copy_inp(src: &Path, dst: &Path, opts: CopyOptions) -> DataObjCopyInp {
+        let src = DataObjInp::new(src.to_str().unwrap().to_owned(), OprType::CopySrc, 0, 0);
+        let mut dst = DataObjInp::new(dst.to_str().unwrap().to_owned(), OprType::CopyDst, 0, 0);
+
+        if let Some(r) = opts.resc {
+            dst.cond_input
+                .add_kw(CondInputKw::DestRescNameKw, r.to_string());
+        }
+
+        if opts.force {
+            dst.cond_input.set_kw(CondInputKw::ForceFlagKw);
+        }
+
+        if opts.checksum {
+            dst.cond_input.set_kw(CondInputKw::RegChksumKw);
+        }
+
+        if opts.verify_checksum {
+            dst.cond_input.set_kw(CondInputKw::VerifyChksumKw);
+        }
+
+        DataObjCopyInp::new(src, dst)
+    }
+
+    async fn copy_data_obj(
+        &mut self,
+        src: &Path,
+        dst: &Path,
+        opts: CopyOptions<'_>,
+    ) -> Result<(), IrodsError> {
+        let inp = Self::make_copy_inp(src, dst, opts);
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::DataObjCopy as i32)
+            .await?;
+
+        self.resources.read_coll_opr_status::<T>().await?;
+
+        Ok(())
+    }
+
+    // Mirrors the tree under `src`, creating each collection before copying
+    // anything into it
+    async fn copy_coll(
+        &mut self,
+        src: &Path,
+        dst: &Path,
+        opts: CopyOptions<'_>,
+    ) -> Result<(), IrodsError> {
+        if dst.starts_with(src) {
+            return Err(IrodsError::Other(
+                "Cannot copy a collection into itself".into(),
+            ));
+        }
+
+        let mut pending = VecDeque::from([(src.to_path_buf(), dst.to_path_buf())]);
+
+        while let Some((src, dst)) = pending.pop_front() {
+            self.ensure_collection(&dst).await?;
+
+            let (data_objects, sub_collections) = self.list_children(&src).await?;
+
+            for data_object in data_objects {
+                let name = data_object.file_name().unwrap();
+                self.copy_data_obj(&data_object, &dst.join(name), opts)
+                    .await?;
+            }
+
+            for sub_collection in sub_collections {
+                let name = sub_collection.file_name().unwrap().to_owned();
+                pending.push_back((sub_collection, dst.join(name)));
+            }
+        }
+
+        Ok(())
+    }
+}
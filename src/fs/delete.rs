@@ -1,10 +1,8 @@
 use std::path::Path;
 
-use tokio::io::{AsyncRead, AsyncWriteExt};
-
 use crate::{
     bosd::ProtocolEncoding,
-    common::{self, cond_input_kw::CondInputKw, ObjectType, APN},
+    common::{cond_input_kw::CondInputKw, ObjectType, APN},
     connection::Connection,
     error::errors::IrodsError,
     msg::{coll::CollInp, data_obj_inp::DataObjInp, header::MsgType},
@@ -119,14 +117,7 @@ where
         self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::RmColl as i32)
             .await?;
 
-        while self.resources.read_standard_header::<T>().await?.int_info
-            == common::response::SVR_TO_CLI_COLL_STAT
-        {
-            self.resources
-                .transport
-                .write_i32(common::response::SVR_TO_CLI_COLL_STAT_REPLY)
-                .await?;
-        }
+        self.resources.read_coll_opr_status::<T>().await?;
 
         Ok(())
     }
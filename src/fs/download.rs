@@ -1,7 +1,14 @@
-use std::{io::Write, os::unix::fs::FileExt, path::Path};
+use std::{
+    collections::{HashSet, VecDeque},
+    ffi::OsString,
+    io::Write,
+    os::unix::fs::FileExt,
+    path::{Path, PathBuf},
+};
 
 use crate::{error::errors::IrodsError, msg::stat::RodsObjStat};
-use futures::{future::BoxFuture, pin_mut, stream::FuturesUnordered, FutureExt, StreamExt};
+use deadpool::managed::Object;
+use futures::{pin_mut, stream::FuturesUnordered, StreamExt};
 use tokio::fs::OpenOptions;
 
 use crate::{
@@ -10,26 +17,38 @@ use crate::{
     connection::{authenticate::Authenticate, connect::Connect, pool::ConnectionPool, Connection},
 };
 
-pub struct ParallelDownloadContext<'pool, 'path, T, C, A>
+use super::transfer::{
+    run_bounded, split_into_slices, CancellationFlag, RetryPolicy, TransferConnection,
+    TransferHandle,
+};
+
+pub struct ParallelDownloadContext<T, C, A>
 where
     T: ProtocolEncoding + Send + Sync,
     C: Connect<T> + Send + Sync + 'static,
     C::Transport: Send + Sync + 'static,
     A: Authenticate<T, C::Transport> + Send + Sync + 'static,
 {
-    pool: &'pool mut ConnectionPool<T, C, A>,
+    pool: ConnectionPool<T, C, A>,
     num_tasks: u32,
-    remote_path: &'path Path,
-    local_path: &'path Path,
+    remote_path: PathBuf,
+    local_path: PathBuf,
     resource: Option<String>,
     force_overwrite: bool,
     create: bool,
     recursive: bool,
     max_collection_children: u32,
     max_size_before_parallel: usize,
+    max_concurrent_downloads: usize,
+    continue_on_error: bool,
+    cancelled: CancellationFlag,
+    retry_policy: RetryPolicy,
+    keep_partial: bool,
+    redirect: bool,
+    portal: bool,
 }
 
-impl<'pool, 'path, T, C, A> ParallelDownloadContext<'pool, 'path, T, C, A>
+impl<T, C, A> ParallelDownloadContext<T, C, A>
 where
     T: ProtocolEncoding + Send + Sync,
     C: Connect<T> + Send + Sync + 'static,
@@ -37,10 +56,10 @@ where
     A: Authenticate<T, C::Transport> + Send + Sync + 'static,
 {
     pub fn new(
-        pool: &'pool mut ConnectionPool<T, C, A>,
+        pool: ConnectionPool<T, C, A>,
         num_tasks: u32,
-        remote_path: &'path Path,
-        local_path: &'path Path,
+        remote_path: PathBuf,
+        local_path: PathBuf,
     ) -> Self {
         Self {
             pool,
@@ -53,6 +72,13 @@ where
             resource: None,
             max_collection_children: 500,
             max_size_before_parallel: 32 * (1024_usize.pow(2)), // Default from PRC
+            max_concurrent_downloads: 8,
+            continue_on_error: false,
+            cancelled: CancellationFlag::default(),
+            retry_policy: RetryPolicy::default(),
+            keep_partial: false,
+            redirect: false,
+            portal: false,
         }
     }
 
@@ -81,51 +107,112 @@ where
         self
     }
 
-    pub async fn download(mut self) -> Result<(), IrodsError> {
+    /// Upper bound on the number of data objects downloaded at once
+    /// during a recursive download.
+    pub fn max_concurrent_downloads(mut self, max: usize) -> Self {
+        self.max_concurrent_downloads = max.max(1);
+        self
+    }
+
+    /// Keep going after a data object fails to download. Every failure is
+    /// reported together once every download has finished.
+    pub fn continue_on_error(mut self) -> Self {
+        self.continue_on_error = true;
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Leave a partially written local file in place when a parallel
+    /// download fails instead of removing it.
+    pub fn keep_partial(mut self) -> Self {
+        self.keep_partial = true;
+        self
+    }
+
+    /// Read the slices of a parallel download straight from the resource
+    /// server holding the replica rather than through the server the pool
+    /// is connected to.
+    pub fn redirect_to_resource_server(mut self) -> Self {
+        self.redirect = true;
+        self
+    }
+
+    /// Receive large data objects through the server's portal, the way the
+    /// iRODS clients do, instead of reading each slice through its own open
+    /// of the data object.
+    pub fn use_portal(mut self) -> Self {
+        self.portal = true;
+        self
+    }
+
+    /// Runs the download as its own task. The context owns everything it
+    /// needs, so several downloads can share one pool.
+    pub fn spawn(self) -> TransferHandle<()>
+    where
+        T: 'static,
+    {
+        let cancelled = self.cancelled.clone();
+
+        TransferHandle::spawn(self.download(), cancelled)
+    }
+
+    pub async fn download(self) -> Result<(), IrodsError> {
         let mut conn = self
             .pool
             .get()
             .await
             .map_err(|_| IrodsError::Other("Failed to get connection".to_string()))?;
 
-        let stat = conn.stat(self.remote_path).await?;
+        let stat = conn.try_stat(&self.remote_path).await?;
+        drop(conn);
+
+        let stat = match stat {
+            Some(stat) => stat,
+            None => return Err(IrodsError::Other("Path does not exist in zone".to_string())),
+        };
 
         match stat.object_type {
             _ if self.local_path.exists() && !self.force_overwrite => Err(IrodsError::Other(
                 "Local path exists and force_overwrite flag is not set".to_string(),
             )),
-            ObjectType::UnknownObj => {
-                Err(IrodsError::Other("Path does not exist in zone".to_string()))
-            }
             ObjectType::DataObj => {
-                let remote_path = self.remote_path;
-                let local_path = self.local_path;
-
-                self.download_data_object(&stat, &remote_path, &local_path)
+                self.download_data_object(&stat, &self.remote_path, &self.local_path)
                     .await
             }
             ObjectType::Coll if !self.recursive => Err(IrodsError::Other(
                 "Collection download without recursive flag".to_string(),
             )),
             ObjectType::Coll => {
-                let local_path = self.local_path;
-                let remote_path = self.remote_path;
-
-                self.download_collection(&remote_path, &local_path).await
+                self.download_collection(&self.remote_path, &self.local_path)
+                    .await
             }
             _ => Err(IrodsError::Other("Invalid path".to_string())),
         }
     }
 
-    pub async fn download_data_object<'this, 'd>(
-        &'this mut self,
-        stat: &'d RodsObjStat,
-        src: &'d Path,
-        dst: &'d Path,
+    pub async fn download_data_object(
+        &self,
+        stat: &RodsObjStat,
+        src: &Path,
+        dst: &Path,
     ) -> Result<(), IrodsError> {
-        if stat.size > self.max_size_before_parallel {
-            self.download_data_object_parallel(src, dst, stat.size as usize)
-                .await
+        self.download_data_object_of_size(src, dst, stat.size).await
+    }
+
+    async fn download_data_object_of_size(
+        &self,
+        src: &Path,
+        dst: &Path,
+        size: usize,
+    ) -> Result<(), IrodsError> {
+        self.cancelled.check()?;
+
+        if size > self.max_size_before_parallel {
+            self.download_data_object_parallel(src, dst, size).await
         } else {
             let mut conn = self
                 .pool
@@ -136,22 +223,34 @@ where
             let file = OpenOptions::new()
                 .create(true)
                 .write(true)
+                .truncate(true)
                 .open(dst)
                 .await?;
 
-            let handle = conn.open_request(src).execute().await?;
-
-            conn.read_data_obj_into_bytes_buf(handle, stat.size as usize)
-                .await?;
+            // Small objects come back in a single reply, so there is no
+            // replica to open and close
+            let mut buf = std::mem::take(&mut conn.resources.bytes_buf);
 
-            let size = stat.size as usize;
+            let request = match self.resource.as_deref() {
+                Some(resc) => conn.get_request(src).set_resc(resc),
+                None => conn.get_request(src),
+            };
+            let result = request.execute(&mut buf).await;
+
+            let len = match result {
+                Ok(len) => len,
+                Err(err) => {
+                    // The reply may have been cut off mid-message, so the
+                    // connection must not go back to the pool
+                    let _ = Object::take(conn);
+                    return Err(err);
+                }
+            };
 
             let mut file = file.into_std().await;
 
-            let mut buf = std::mem::take(&mut conn.resources.bytes_buf);
-
             let buf = tokio::task::spawn_blocking(move || {
-                file.write_all(&mut buf[..size])?;
+                file.write_all(&buf[..len])?;
                 file.sync_all()?;
                 Ok::<_, IrodsError>(buf)
             })
@@ -160,16 +259,14 @@ where
 
             conn.resources.bytes_buf = buf;
 
-            conn.close(handle).await?;
-
             Ok(())
         }
     }
 
-    pub async fn stat_and_download_data_object<'this, 'd>(
-        &'this mut self,
-        src: &'d Path,
-        dst: &'d Path,
+    pub async fn stat_and_download_data_object(
+        &self,
+        src: &Path,
+        dst: &Path,
     ) -> Result<(), IrodsError> {
         let mut conn = self
             .pool
@@ -182,109 +279,269 @@ where
         self.download_data_object(&stat, src, dst).await
     }
 
-    pub fn download_collection<'this, 'd>(
-        &'this mut self,
-        src: &'d Path,
-        dst: &'d Path,
-    ) -> BoxFuture<'this, Result<(), IrodsError>>
-    // Boxing and sync signature needed to
-    // A) prevent the async statement from
-    // compiling a cycle on recursion
-    // B) running afowl of the borrow checker, i.e.,
-    // we can manually specify the lifetimes
-    where
-        'd: 'this,
-    {
-        async move {
-            if dst.exists() && self.force_overwrite {
-                tokio::fs::remove_dir_all(dst).await?
+    pub async fn download_collection(&self, src: &Path, dst: &Path) -> Result<(), IrodsError> {
+        if dst.exists() && self.force_overwrite {
+            tokio::fs::remove_dir_all(dst).await?
+        }
+        tokio::fs::create_dir(dst).await?;
+
+        let (small, large) = self.walk_collection(src, dst).await?;
+
+        let download = |(src, dst, size): (PathBuf, PathBuf, usize)| async move {
+            let result = self.download_data_object_of_size(&src, &dst, size).await;
+            (src, result)
+        };
+
+        let mut failures = Vec::new();
+
+        run_bounded(
+            small,
+            self.max_concurrent_downloads,
+            self.continue_on_error,
+            &self.cancelled,
+            &mut failures,
+            download,
+        )
+        .await?;
+
+        // Large objects are already split across `num_tasks` connections,
+        // so running them side by side could starve the pool.
+        run_bounded(
+            large,
+            1,
+            self.continue_on_error,
+            &self.cancelled,
+            &mut failures,
+            download,
+        )
+        .await?;
+
+        match failures.is_empty() {
+            true => Ok(()),
+            false => Err(IrodsError::TransferFailed(failures)),
+        }
+    }
+
+    /// Walks the collection breadth-first, creating each local directory
+    /// before any of its children are visited. Returns the data objects to
+    /// download, split on `max_size_before_parallel`.
+    async fn walk_collection(
+        &self,
+        src: &Path,
+        dst: &Path,
+    ) -> Result<
+        (
+            Vec<(PathBuf, PathBuf, usize)>,
+            Vec<(PathBuf, PathBuf, usize)>,
+        ),
+        IrodsError,
+    > {
+        let mut small = Vec::new();
+        let mut large = Vec::new();
+
+        let mut pending = VecDeque::from([(src.to_path_buf(), dst.to_path_buf())]);
+
+        while let Some((src, dst)) = pending.pop_front() {
+            let (data_objects, sub_collections) = self.list_collection(&src).await?;
+
+            for (name, size) in data_objects {
+                let transfer = (src.join(&name), dst.join(&name), size);
+
+                if size > self.max_size_before_parallel {
+                    large.push(transfer);
+                } else {
+                    small.push(transfer);
+                }
             }
-            tokio::fs::create_dir(dst).await?;
 
-            let mut conn = self
-                .pool
-                .get()
-                .await
-                .map_err(|_| IrodsError::Other("Failed to get connection".to_string()))?;
+            for name in sub_collections {
+                let local_path = dst.join(&name);
+                tokio::fs::create_dir(&local_path).await?;
+
+                pending.push_back((src.join(&name), local_path));
+            }
+        }
+
+        Ok((small, large))
+    }
+
+    /// Lists the names and sizes of the data objects in `src` and the names
+    /// of its sub-collections. The pooled connection is only held for the
+    /// listing, so a long walk does not keep it from the downloads.
+    async fn list_collection(
+        &self,
+        src: &Path,
+    ) -> Result<(Vec<(OsString, usize)>, Vec<OsString>), IrodsError> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|_| IrodsError::Other("Failed to get connection".to_string()))?;
 
-            let data_objects = conn
+        let mut data_objects = Vec::new();
+
+        {
+            let stream = conn
                 .ls_data_objects(src, self.max_collection_children, false, None)
                 .await;
 
-            pin_mut!(data_objects);
+            pin_mut!(stream);
+
+            // The catalog returns one row per replica
+            let mut seen = HashSet::new();
 
-            while let Some(data_object) = data_objects.next().await {
+            while let Some(data_object) = stream.next().await {
                 let data_object = data_object?;
-                let remote_path = src.join(data_object.path.file_name().unwrap());
-                let local_path = dst.join(data_object.path.file_name().unwrap());
+                let name = data_object.path.file_name().unwrap().to_owned();
 
-                self.stat_and_download_data_object(&remote_path, &local_path)
-                    .await?;
+                if seen.insert(name.clone()) {
+                    data_objects.push((name, data_object.size));
+                }
             }
+        }
 
-            let mut conn = self
-                .pool
-                .get()
-                .await
-                .map_err(|_| IrodsError::Other("Failed to get connection".to_string()))?;
+        let mut sub_collections = Vec::new();
 
-            let sub_collections = conn
-                .ls_sub_collections(src, self.max_collection_children)
-                .await;
+        let stream = conn
+            .ls_sub_collections(src, self.max_collection_children)
+            .await;
+
+        pin_mut!(stream);
+
+        while let Some(sub_collection) = stream.next().await {
+            let sub_collection = sub_collection?;
+            sub_collections.push(sub_collection.path.file_name().unwrap().to_owned());
+        }
+
+        Ok((data_objects, sub_collections))
+    }
 
-            pin_mut!(sub_collections);
+    pub async fn download_data_object_parallel(
+        &self,
+        remote_path: &Path,
+        local_path: &Path,
+        size: usize,
+    ) -> Result<(), IrodsError> {
+        if self.portal {
+            return self
+                .download_data_object_via_portal(remote_path, local_path)
+                .await;
+        }
 
-            while let Some(sub_collection) = sub_collections.next().await {
-                let sub_collection = sub_collection?;
+        let host = match self.redirect {
+            true => self.host_for_get(remote_path).await?,
+            false => None,
+        };
+        let host = host.as_deref();
+
+        let slices = split_into_slices(size, self.num_tasks);
+        let num_slices = slices.len();
+
+        let mut futs: FuturesUnordered<_> = slices
+            .into_iter()
+            .enumerate()
+            .map(|(slice, (offset, len))| async move {
+                let result = self
+                    .download_slice(remote_path, local_path, host, offset, len)
+                    .await;
+                (slice, result)
+            })
+            .collect();
 
-                let remote_path = src.join(sub_collection.path.file_name().unwrap());
-                let local_path = dst.join(sub_collection.path.file_name().unwrap());
+        let mut failures = Vec::new();
 
-                self.download_collection(&remote_path, &local_path).await?;
+        while let Some((slice, result)) = futs.next().await {
+            if let Err(err) = result {
+                failures.push((slice, err));
             }
+        }
 
-            Ok(())
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        if !self.keep_partial {
+            let _ = tokio::fs::remove_file(local_path).await;
         }
-        .boxed()
+
+        Err(IrodsError::SlicesFailed {
+            path: remote_path.to_path_buf(),
+            num_slices,
+            failures,
+        })
     }
 
-    pub async fn download_data_object_parallel<'this, 'd>(
-        &'this mut self,
-        local_path: &'d Path,
-        remote_path: &'d Path,
-        size: usize,
+    async fn download_data_object_via_portal(
+        &self,
+        remote_path: &Path,
+        local_path: &Path,
     ) -> Result<(), IrodsError> {
-        let len_per_task = ((size as f64 / self.num_tasks as f64).floor() as usize)
-            + ((((size as u32) % self.num_tasks != 0) as usize) as usize);
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|_| IrodsError::Other("Failed to get connection".to_string()))?;
 
-        let futs = FuturesUnordered::new();
-        for task in 0..self.num_tasks {
-            let mut conn = self
-                .pool
-                .get()
-                .await
-                .map_err(|_| IrodsError::Other("Failed to get connection".to_string()))?;
+        let request = conn.get_request(remote_path).num_threads(self.num_tasks);
+        let request = match self.resource.as_deref() {
+            Some(resc) => request.set_resc(resc),
+            None => request,
+        };
+        let result = request.execute_into_file(local_path).await;
 
-            let resource = self.resource.clone();
-            let remote_path = remote_path.to_path_buf();
-            let local_path = local_path.to_path_buf();
-
-            futs.push(async move {
-                conn.do_parallel_download_task(
-                    &remote_path,
-                    &local_path,
-                    resource,
-                    task as usize,
-                    len_per_task,
-                )
-                .await?;
+        if result.is_err() {
+            let _ = Object::take(conn);
 
-                Ok::<_, IrodsError>(())
-            });
+            if !self.keep_partial {
+                let _ = tokio::fs::remove_file(local_path).await;
+            }
         }
 
-        futs.collect::<Vec<_>>().await;
-        Ok(())
+        result
+    }
+
+    async fn host_for_get(&self, remote_path: &Path) -> Result<Option<String>, IrodsError> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|_| IrodsError::Other("Failed to get connection".to_string()))?;
+
+        conn.get_host_for_get(remote_path, self.resource.as_deref())
+            .await
+    }
+
+    async fn download_slice(
+        &self,
+        remote_path: &Path,
+        local_path: &Path,
+        host: Option<&str>,
+        offset: usize,
+        len: usize,
+    ) -> Result<(), IrodsError> {
+        self.retry_policy
+            .run(&self.cancelled, || async {
+                let mut conn = TransferConnection::get(&self.pool, host).await?;
+
+                let result = conn
+                    .do_parallel_download_task(
+                        remote_path,
+                        local_path,
+                        self.resource.clone(),
+                        offset,
+                        len,
+                    )
+                    .await;
+
+                // The connection may have failed mid-message, so it
+                // must not go back to the pool
+                if result.is_err() {
+                    conn.discard();
+                }
+
+                result
+            })
+            .await
     }
 }
 
@@ -298,7 +555,7 @@ where
         remote_path: &Path,
         local_path: &Path,
         resource: Option<String>,
-        task: usize,
+        offset: usize,
         len: usize,
     ) -> Result<(), IrodsError> {
         let file = OpenOptions::new()
@@ -309,8 +566,6 @@ where
 
         let handle = self.open_request(remote_path).execute().await?;
 
-        let offset = task * len;
-
         if offset > 0 {
             self.seek(handle, super::Whence::SeekSet, offset).await?;
         }
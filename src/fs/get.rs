@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{data_obj_inp::DataObjInp, header::MsgType, portal_opr_out::PortalOprOut},
+};
+
+use super::{OpenFlag, OprType};
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Reads a whole data object in a single request. Meant for objects
+    /// small enough that the server returns them in one buffer.
+    pub fn get_request<'conn, 'p>(&'conn mut self, path: &'p Path) -> GetRequest<'conn, 'p, T, C> {
+        GetRequest::new(self, path)
+    }
+
+    fn make_get_data_obj_inp(path: &Path, resc: Option<&str>, num_threads: u32) -> DataObjInp {
+        let mut inp = DataObjInp::new(
+            path.to_str().unwrap().to_owned(),
+            OprType::Get,
+            OpenFlag::ReadOnly as i32,
+            0,
+        );
+        inp.num_threads = num_threads as i32;
+
+        if let Some(r) = resc {
+            inp.cond_input
+                .add_kw(CondInputKw::RescNameKw, r.to_string());
+        }
+
+        inp
+    }
+}
+
+pub struct GetRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    path: &'p Path,
+    resc: Option<&'p str>,
+    num_threads: u32,
+}
+
+impl<'conn, 'p, T, C> GetRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, path: &'p Path) -> Self {
+        Self {
+            conn,
+            path,
+            resc: None,
+            num_threads: 0,
+        }
+    }
+
+    pub fn set_resc(mut self, resc: &'p str) -> Self {
+        self.resc = Some(resc);
+        self
+    }
+
+    /// Number of portal sockets to ask for when receiving into a file. Zero
+    /// leaves the choice to the server.
+    pub fn num_threads(mut self, num_threads: u32) -> Self {
+        self.num_threads = num_threads;
+        self
+    }
+
+    /// Writes the contents of the data object to the start of `sink` and
+    /// returns its length.
+    pub async fn execute(self, sink: &mut Vec<u8>) -> Result<usize, IrodsError> {
+        self.conn
+            .resources
+            .send_header_then_msg::<T, _>(
+                &Connection::<T, C>::make_get_data_obj_inp(self.path, self.resc, 0),
+                MsgType::RodsApiReq,
+                APN::DataObjGet as i32,
+            )
+            .await?;
+
+        let (header, out) = self
+            .conn
+            .resources
+            .get_header_msg_and_bytes::<T, PortalOprOut>(sink)
+            .await?;
+
+        // The server has opened the object for a portal. Completing the
+        // operation closes it, which a failed one would leave to the end of
+        // the connection. Nothing was read, so the replica is untouched.
+        if out.num_threads > 0 {
+            self.conn.complete_portal_opr(out.l1_desc_inx, true).await?;
+
+            return Err(IrodsError::Other(
+                "Server requested a parallel transfer for a single-buffer get".into(),
+            ));
+        }
+
+        Ok(header.bs_len)
+    }
+
+    /// Writes the data object to `local_path`. Large objects come through
+    /// the server's portal, the way the iRODS clients transfer them, over
+    /// separate sockets that are encrypted when the connection uses SSL.
+    pub async fn execute_into_file(self, local_path: &Path) -> Result<(), IrodsError> {
+        self.conn
+            .resources
+            .send_header_then_msg::<T, _>(
+                &Connection::<T, C>::make_get_data_obj_inp(self.path, self.resc, self.num_threads),
+                MsgType::RodsApiReq,
+                APN::DataObjGet as i32,
+            )
+            .await?;
+
+        let mut buf = std::mem::take(&mut self.conn.resources.bytes_buf);
+
+        let result = self
+            .conn
+            .resources
+            .get_header_msg_and_bytes::<T, PortalOprOut>(&mut buf)
+            .await;
+
+        let (header, out) = match result {
+            Ok(reply) => reply,
+            Err(err) => {
+                self.conn.resources.bytes_buf = buf;
+                return Err(err);
+            }
+        };
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(local_path)
+            .await?;
+
+        // Small objects arrive with the reply
+        if out.num_threads <= 0 {
+            let written = file.write_all(&buf[..header.bs_len]).await;
+            self.conn.resources.bytes_buf = buf;
+            written?;
+
+            return Ok(());
+        }
+
+        self.conn.resources.bytes_buf = buf;
+
+        self.conn
+            .get_from_portal(
+                &out.port_list,
+                out.num_threads as usize,
+                out.l1_desc_inx,
+                local_path,
+            )
+            .await
+    }
+}
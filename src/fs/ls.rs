@@ -1,4 +1,7 @@
-use std::path::Path;
+use std::{
+    collections::{HashSet, VecDeque},
+    path::{Path, PathBuf},
+};
 
 use async_stream::try_stream;
 use futures::{pin_mut, Stream, StreamExt, TryFuture};
@@ -97,6 +100,9 @@ where
             .select(IcatColumn::CollectionOwnerName)
             .select(IcatColumn::CollectionModifyTime)
             .select(IcatColumn::CollectionCreateTime)
+            .select(IcatColumn::CollectionType)
+            .select(IcatColumn::CollectionInfo1)
+            .select(IcatColumn::CollectionInfo2)
             .condition(
                 IcatColumn::CollectionParentName,
                 IcatPredicate::Equals(path.to_str().unwrap().to_owned()),
@@ -111,4 +117,56 @@ where
             }
         }
     }
+
+    /// Paths of the data objects and sub-collections directly under `path`.
+    /// Data objects are listed once no matter how many replicas they have.
+    pub(crate) async fn list_children(
+        &mut self,
+        path: &Path,
+    ) -> Result<(Vec<PathBuf>, Vec<PathBuf>), IrodsError> {
+        let mut data_objects = Vec::new();
+        let mut sub_collections = Vec::new();
+
+        {
+            let rows = self.ls_data_objects(path, 500, false, None).await;
+            pin_mut!(rows);
+
+            let mut seen = HashSet::new();
+
+            while let Some(data_object) = rows.next().await {
+                let data_object = data_object?;
+
+                if seen.insert(data_object.path.clone()) {
+                    data_objects.push(data_object.path);
+                }
+            }
+        }
+
+        let rows = self.ls_sub_collections(path, 500).await;
+        pin_mut!(rows);
+
+        while let Some(sub_collection) = rows.next().await {
+            sub_collections.push(sub_collection?.path);
+        }
+
+        Ok((data_objects, sub_collections))
+    }
+
+    /// Paths of every data object in the tree under `path`.
+    pub(crate) async fn list_data_objects_recursive(
+        &mut self,
+        path: &Path,
+    ) -> Result<Vec<PathBuf>, IrodsError> {
+        let mut data_objects = Vec::new();
+        let mut pending = VecDeque::from([path.to_path_buf()]);
+
+        while let Some(coll) = pending.pop_front() {
+            let (children, sub_collections) = self.list_children(&coll).await?;
+
+            data_objects.extend(children);
+            pending.extend(sub_collections);
+        }
+
+        Ok(data_objects)
+    }
 }
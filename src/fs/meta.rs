@@ -13,7 +13,7 @@ use crate::{
         header::MsgType,
         meta::ModAVURequest,
     },
-    AVUOperation, AVUTarget, AVU,
+    AVUColumns, AVUOperation, AVUTarget, AVU,
 };
 
 impl<T, C> Connection<T, C>
@@ -28,11 +28,13 @@ where
     where
         'p: 'this,
     {
+        let columns = AVUColumns::for_target(AVUTarget::DataObject);
+
         let mut inp = QueryBuilder::new()
-            .select(IcatColumn::MetadataAttributeId)
-            .select(IcatColumn::MetadataAttributeName)
-            .select(IcatColumn::MetadataAttributeValue)
-            .select(IcatColumn::MetadataAttributeUnits)
+            .select(columns.id)
+            .select(columns.attribute)
+            .select(columns.value)
+            .select(columns.unit)
             .condition(
                 IcatColumn::DataObjectBaseName,
                 IcatPredicate::Equals(path.file_name().unwrap().to_str().unwrap().to_string()),
@@ -47,7 +49,7 @@ where
             let stream = self.query(&mut inp).await;
             for await row in stream {
                 let mut row = row?;
-                yield AVU::try_from_row(&mut row)?;
+                yield AVU::try_from_row(&mut row, &columns)?;
             }
         }
     }
@@ -59,22 +61,53 @@ where
     where
         'p: 'this,
     {
+        self.list_avus_by(
+            AVUTarget::Collection,
+            IcatColumn::CollectionName,
+            path.to_str().unwrap().to_string(),
+        )
+    }
+
+    pub async fn list_avus_for_user<'this>(
+        &'this mut self,
+        user: &str,
+    ) -> impl Stream<Item = Result<AVU, IrodsError>> + 'this {
+        self.list_avus_by(AVUTarget::User, IcatColumn::UserName, user.to_string())
+    }
+
+    pub async fn list_avus_for_resource<'this>(
+        &'this mut self,
+        resc: &str,
+    ) -> impl Stream<Item = Result<AVU, IrodsError>> + 'this {
+        self.list_avus_by(
+            AVUTarget::Resource,
+            IcatColumn::ResourceName,
+            resc.to_string(),
+        )
+    }
+
+    // For targets identified by a single column
+    fn list_avus_by<'this>(
+        &'this mut self,
+        target: AVUTarget,
+        name_column: IcatColumn,
+        name: String,
+    ) -> impl Stream<Item = Result<AVU, IrodsError>> + 'this {
+        let columns = AVUColumns::for_target(target);
+
         let mut inp = QueryBuilder::new()
-            .select(IcatColumn::MetadataAttributeId)
-            .select(IcatColumn::MetadataAttributeName)
-            .select(IcatColumn::MetadataAttributeValue)
-            .select(IcatColumn::MetadataAttributeUnits)
-            .condition(
-                IcatColumn::CollectionName,
-                IcatPredicate::Equals(path.to_str().unwrap().to_string()),
-            )
+            .select(columns.id)
+            .select(columns.attribute)
+            .select(columns.value)
+            .select(columns.unit)
+            .condition(name_column, IcatPredicate::Equals(name))
             .build();
 
         try_stream! {
             let stream = self.query(&mut inp).await;
             for await row in stream {
                 let mut row = row?;
-                yield AVU::try_from_row(&mut row)?;
+                yield AVU::try_from_row(&mut row, &columns)?;
             }
         }
     }
@@ -87,12 +120,7 @@ where
     ) -> Result<(), IrodsError> {
         let inp = ModAVURequest::new(AVUOperation::Add, target_type, target_name, avu, None);
 
-        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::ModAvu as i32)
-            .await?;
-
-        self.resources.read_standard_header::<T>().await?;
-
-        Ok(())
+        self.mod_avu(&inp).await
     }
 
     pub async fn remove_avu<'this, 'p>(
@@ -103,10 +131,107 @@ where
     ) -> Result<(), IrodsError> {
         let inp = ModAVURequest::new(AVUOperation::Remove, target_type, target_name, avu, None);
 
-        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::ModAvu as i32)
+        self.mod_avu(&inp).await
+    }
+
+    /// Replaces every AVU with the attribute of `avu` by `avu`.
+    pub async fn set_avu(
+        &mut self,
+        target_type: AVUTarget,
+        target_name: String,
+        avu: AVU,
+    ) -> Result<(), IrodsError> {
+        let inp = ModAVURequest::new(AVUOperation::Set, target_type, target_name, avu, None);
+
+        self.mod_avu(&inp).await
+    }
+
+    /// Changes the fields of `avu` that are not empty in `new_avu`.
+    pub async fn modify_avu(
+        &mut self,
+        target_type: AVUTarget,
+        target_name: String,
+        avu: AVU,
+        new_avu: AVU,
+    ) -> Result<(), IrodsError> {
+        if new_avu.attribute.is_empty() && new_avu.value.is_empty() && new_avu.unit.is_empty() {
+            return Err(IrodsError::Other("Nothing to modify".into()));
+        }
+
+        let inp = ModAVURequest::new(
+            AVUOperation::Modify,
+            target_type,
+            target_name,
+            avu,
+            Some(new_avu),
+        );
+
+        self.mod_avu(&inp).await
+    }
+
+    /// Adds `avu` to every data object whose path matches `pattern`, in
+    /// which `%` matches any run of characters and `_` any one character.
+    pub async fn add_avu_wildcard(&mut self, pattern: String, avu: AVU) -> Result<(), IrodsError> {
+        let inp = ModAVURequest::new(
+            AVUOperation::AddWildcard,
+            AVUTarget::DataObject,
+            pattern,
+            avu,
+            None,
+        );
+
+        self.mod_avu(&inp).await
+    }
+
+    /// Removes the AVUs whose attribute and value match those of `avu`,
+    /// where `%` and `_` are wildcards.
+    pub async fn remove_avu_wildcard(
+        &mut self,
+        target_type: AVUTarget,
+        target_name: String,
+        avu: AVU,
+    ) -> Result<(), IrodsError> {
+        let inp = ModAVURequest::new(
+            AVUOperation::RemoveWildcard,
+            target_type,
+            target_name,
+            avu,
+            None,
+        );
+
+        self.mod_avu(&inp).await
+    }
+
+    /// Removes the AVU with the catalog id `id`, as read into `AVU::id`.
+    pub async fn remove_avu_by_id(
+        &mut self,
+        target_type: AVUTarget,
+        target_name: String,
+        id: i64,
+    ) -> Result<(), IrodsError> {
+        let inp = ModAVURequest::remove_by_id(target_type, target_name, id);
+
+        self.mod_avu(&inp).await
+    }
+
+    /// Copies every AVU of one target to another.
+    pub async fn copy_avus(
+        &mut self,
+        src_type: AVUTarget,
+        src_name: String,
+        dst_type: AVUTarget,
+        dst_name: String,
+    ) -> Result<(), IrodsError> {
+        let inp = ModAVURequest::copy(src_type, dst_type, src_name, dst_name);
+
+        self.mod_avu(&inp).await
+    }
+
+    async fn mod_avu(&mut self, inp: &ModAVURequest) -> Result<(), IrodsError> {
+        self.send_header_then_msg(inp, MsgType::RodsApiReq, APN::ModAvu as i32)
             .await?;
 
-        self.resources.read_standard_header::<T>().await?;
+        self.resources.read_status::<T>().await?;
 
         Ok(())
     }
@@ -1,15 +1,31 @@
 pub mod acls;
 pub mod admin;
+pub mod atomic;
+pub mod bundle;
 pub mod close;
+pub mod coll;
+pub mod copy;
 pub mod delete;
 pub mod download;
+pub mod get;
 pub mod ls;
 pub mod meta;
+pub mod modify;
+pub mod mount;
 pub mod open;
+pub mod phymv;
+pub mod portal;
+pub mod put;
 pub mod read;
+pub mod redirect;
+pub mod reg;
+pub mod rename;
+pub mod replica;
 pub mod resc;
+pub mod search;
 pub mod seek;
 pub mod stat;
+pub mod transfer;
 pub mod upload;
 pub mod write;
 
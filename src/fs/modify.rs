@@ -0,0 +1,253 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{
+        bytes_buf::BytesBuf, data_obj_inp::DataObjInp, header::MsgType,
+        mod_data_obj_meta::ModDataObjMetaInp,
+    },
+    DataObjectType,
+};
+
+use super::OprType;
+
+#[derive(Serialize)]
+struct TouchInp<'p> {
+    logical_path: &'p str,
+    options: TouchOptions<'p>,
+}
+
+#[derive(Serialize, Default)]
+struct TouchOptions<'p> {
+    no_create: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replica_number: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    leaf_resource_name: Option<&'p str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seconds_since_epoch: Option<i64>,
+}
+
+pub struct TouchRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    path: &'p Path,
+    opts: TouchOptions<'p>,
+}
+
+impl<'conn, 'p, T, C> TouchRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, path: &'p Path) -> Self {
+        Self {
+            conn,
+            path,
+            opts: TouchOptions::default(),
+        }
+    }
+
+    /// Fail instead of creating an empty data object when `path` is missing.
+    pub fn no_create(mut self, no_create: bool) -> Self {
+        self.opts.no_create = no_create;
+        self
+    }
+
+    /// Only update the replica with this number.
+    pub fn replica_number(mut self, replica_number: u32) -> Self {
+        self.opts.replica_number = Some(replica_number);
+        self
+    }
+
+    /// Only update the replica on this leaf resource, which is also where a
+    /// new data object is created.
+    pub fn leaf_resc(mut self, resc: &'p str) -> Self {
+        self.opts.leaf_resource_name = Some(resc);
+        self
+    }
+
+    /// Set the modify time to `mtime` rather than now.
+    pub fn mtime(mut self, mtime: DateTime<Utc>) -> Self {
+        self.opts.seconds_since_epoch = Some(mtime.timestamp());
+        self
+    }
+
+    pub async fn execute(self) -> Result<(), IrodsError> {
+        let inp = TouchInp {
+            logical_path: self.path.to_str().unwrap(),
+            options: self.opts,
+        };
+        let inp = BytesBuf::new(&serde_json::to_string(&inp)?);
+
+        self.conn
+            .resources
+            .send_header_then_msg::<T, _>(&inp, MsgType::RodsApiReq, APN::Touch as i32)
+            .await?;
+
+        self.conn.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+}
+
+/// Changes the system metadata of a data object's replicas, all of them
+/// unless a replica number is given.
+pub struct ModifyDataObjectRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    path: &'p Path,
+    replica_number: Option<u32>,
+    comments: Option<&'p str>,
+    expiry: Option<DateTime<Utc>>,
+    data_type: Option<DataObjectType>,
+    modify_time: Option<DateTime<Utc>>,
+}
+
+impl<'conn, 'p, T, C> ModifyDataObjectRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, path: &'p Path) -> Self {
+        Self {
+            conn,
+            path,
+            replica_number: None,
+            comments: None,
+            expiry: None,
+            data_type: None,
+            modify_time: None,
+        }
+    }
+
+    pub fn replica_number(mut self, replica_number: u32) -> Self {
+        self.replica_number = Some(replica_number);
+        self
+    }
+
+    pub fn comments(mut self, comments: &'p str) -> Self {
+        self.comments = Some(comments);
+        self
+    }
+
+    pub fn expiry(mut self, expiry: DateTime<Utc>) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    pub fn data_type(mut self, data_type: DataObjectType) -> Self {
+        self.data_type = Some(data_type);
+        self
+    }
+
+    pub fn modify_time(mut self, modify_time: DateTime<Utc>) -> Self {
+        self.modify_time = Some(modify_time);
+        self
+    }
+
+    fn make_inp(&self) -> ModDataObjMetaInp {
+        let mut inp = ModDataObjMetaInp::new(self.path.to_str().unwrap().to_owned());
+
+        match self.replica_number {
+            Some(n) => inp.replica_number = n as i32,
+            None => inp.reg_param.set_kw(CondInputKw::AllKw),
+        }
+
+        if let Some(comments) = self.comments {
+            inp.reg_param
+                .add_kw(CondInputKw::DataCommentsKw, comments.to_string());
+        }
+
+        if let Some(expiry) = self.expiry {
+            inp.reg_param
+                .add_kw(CondInputKw::DataExpiryKw, catalog_time(expiry));
+        }
+
+        if let Some(data_type) = self.data_type {
+            let data_type: &str = data_type.into();
+            inp.reg_param
+                .add_kw(CondInputKw::DataTypeKw, data_type.to_string());
+        }
+
+        if let Some(modify_time) = self.modify_time {
+            inp.reg_param
+                .add_kw(CondInputKw::DataModifyKw, catalog_time(modify_time));
+        }
+
+        inp
+    }
+
+    pub async fn execute(self) -> Result<(), IrodsError> {
+        let inp = self.make_inp();
+
+        self.conn
+            .resources
+            .send_header_then_msg::<T, _>(&inp, MsgType::RodsApiReq, APN::ModDataObjMeta as i32)
+            .await?;
+
+        self.conn.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+}
+
+// The catalog stores times as zero-padded seconds since the epoch
+fn catalog_time(time: DateTime<Utc>) -> String {
+    format!("{:011}", time.timestamp())
+}
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Cuts or extends a data object to `size` bytes.
+    pub async fn truncate(&mut self, path: &Path, size: usize) -> Result<(), IrodsError> {
+        let mut inp = DataObjInp::new(path.to_str().unwrap().to_owned(), OprType::No, 0, 0);
+        inp.data_size = size as i64;
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::DataObjTruncate as i32)
+            .await?;
+
+        self.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+
+    /// Updates the modify time of a data object, creating it if missing.
+    pub fn touch<'conn, 'p>(&'conn mut self, path: &'p Path) -> TouchRequest<'conn, 'p, T, C> {
+        TouchRequest::new(self, path)
+    }
+
+    pub fn modify_data_object<'conn, 'p>(
+        &'conn mut self,
+        path: &'p Path,
+    ) -> ModifyDataObjectRequest<'conn, 'p, T, C> {
+        ModifyDataObjectRequest::new(self, path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn catalog_time_is_padded() {
+        let time = DateTime::<Utc>::from_timestamp(86400, 0).unwrap();
+
+        assert_eq!(catalog_time(time), "00000086400");
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{
+        coll::CollInp, data_obj_inp::DataObjInp, header::MsgType, spec_coll::SpecialCollectionClass,
+    },
+};
+
+use super::OprType;
+
+// Collection types as the catalog records them
+const LINK_POINT: &str = "linkPoint";
+const TAR_STRUCT_FILE: &str = "tarStructFile";
+
+// Clears a catalog field
+const NULL_SPECIAL_VALUE: &str = "NULL_SPECIAL_VALUE";
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Mounts the directory `dir` in the vault of `resc` at `collection`,
+    /// which must be empty or missing.
+    pub async fn mount_directory(
+        &mut self,
+        dir: &Path,
+        collection: &Path,
+        resc: &str,
+    ) -> Result<(), IrodsError> {
+        self.register(dir, collection)
+            .set_resc(resc)
+            .mount(true)
+            .execute()
+            .await
+    }
+
+    /// Makes `collection` an alias of the collection `target`.
+    pub async fn link_collection(
+        &mut self,
+        target: &Path,
+        collection: &Path,
+    ) -> Result<(), IrodsError> {
+        self.register(target, collection)
+            .collection_type(LINK_POINT)
+            .execute()
+            .await
+    }
+
+    /// Mounts the tar data object `archive` at `collection`, so its
+    /// contents can be read and written without extracting it.
+    pub async fn mount_struct_file(
+        &mut self,
+        archive: &Path,
+        collection: &Path,
+        resc: Option<&str>,
+    ) -> Result<(), IrodsError> {
+        let request = self
+            .register(archive, collection)
+            .collection_type(TAR_STRUCT_FILE);
+        let request = match resc {
+            Some(resc) => request.set_resc(resc),
+            None => request,
+        };
+
+        request.execute().await
+    }
+
+    /// Writes the cached contents of a mounted structured file back to the
+    /// archive.
+    pub async fn sync_mounted_collection(&mut self, collection: &Path) -> Result<(), IrodsError> {
+        let inp = DataObjInp::new(collection.to_str().unwrap().to_owned(), OprType::No, 0, 0);
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::SyncMountedColl as i32)
+            .await?;
+
+        self.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+
+    /// Turns a mounted or linked collection back into an ordinary, empty
+    /// one. A structured file is synced first so no changes are lost.
+    pub async fn unmount(&mut self, collection: &Path) -> Result<(), IrodsError> {
+        let stat = self.stat(collection).await?;
+
+        match stat.spec_coll {
+            Some(spec_coll) if spec_coll.class == SpecialCollectionClass::StructFile => {
+                self.sync_mounted_collection(collection).await?;
+            }
+            Some(_) => {}
+            None => return Err(IrodsError::Other("Not a special collection".into())),
+        }
+
+        let mut inp = CollInp::builder().build();
+        inp.name = collection.to_str().unwrap().to_owned();
+
+        for kw in [
+            CondInputKw::CollectionTypeKw,
+            CondInputKw::CollectionInfo1Kw,
+            CondInputKw::CollectionInfo2Kw,
+        ] {
+            inp.cond_input.add_kw(kw, NULL_SPECIAL_VALUE.into());
+        }
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::ModColl as i32)
+            .await?;
+
+        self.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+}
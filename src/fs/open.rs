@@ -21,12 +21,23 @@ where
         OpenRequest::new(self, path)
     }
 
-    fn make_open_data_obj_inp(path: &Path, flags: i32, resc: Option<&str>) -> DataObjInp {
+    fn make_open_data_obj_inp(
+        path: &Path,
+        flags: i32,
+        resc: Option<&str>,
+        replica: Option<(&str, &str)>,
+    ) -> DataObjInp {
         let mut inp = DataObjInp::new(path.to_str().unwrap().to_owned(), OprType::No, flags, 0);
         if let Some(r) = resc {
             inp.cond_input
                 .add_kw(CondInputKw::RescNameKw, r.to_string());
         }
+        if let Some((token, hier)) = replica {
+            inp.cond_input
+                .add_kw(CondInputKw::ReplicaTokenKw, token.to_string());
+            inp.cond_input
+                .add_kw(CondInputKw::RescHierStrKw, hier.to_string());
+        }
         inp.data_size = -1;
 
         inp
@@ -37,17 +48,17 @@ where
         path: &Path,
         flags: i32,
         resc: Option<&str>,
+        replica: Option<(&str, &str)>,
     ) -> Result<DataObjectHandle, IrodsError> {
         self.resources
             .send_header_then_msg::<T, _>(
-                &Self::make_open_data_obj_inp(path, flags, resc),
+                &Self::make_open_data_obj_inp(path, flags, resc, replica),
                 MsgType::RodsApiReq,
                 APN::DataObjOpen as i32,
             )
             .await?;
-        let header = self.resources.read_standard_header::<T>().await?;
 
-        Ok(header.int_info)
+        self.resources.read_status::<T>().await
     }
 }
 
@@ -59,6 +70,7 @@ where
     conn: &'conn mut Connection<T, C>,
     flags: i32,
     resc: Option<&'conn str>,
+    replica: Option<(&'conn str, &'conn str)>,
     path: &'conn Path,
 }
 
@@ -72,6 +84,7 @@ where
             conn,
             flags: 0,
             resc: None,
+            replica: None,
             path,
         }
     }
@@ -91,7 +104,16 @@ where
         self
     }
 
+    /// Opens the replica another handle already has open for writing, as
+    /// identified by its `FileDescriptorInfo`.
+    pub fn set_replica_token(mut self, token: &'conn str, resc_hier: &'conn str) -> Self {
+        self.replica = Some((token, resc_hier));
+        self
+    }
+
     pub async fn execute(self) -> Result<DataObjectHandle, IrodsError> {
-        self.conn.open_inner(self.path, self.flags, self.resc).await
+        self.conn
+            .open_inner(self.path, self.flags, self.resc, self.replica)
+            .await
     }
 }
@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{data_obj_inp::DataObjInp, header::MsgType},
+};
+
+use super::{
+    resc::{record_outcome, Outcomes},
+    OprType,
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PhymvOptions<'p> {
+    src_resc: Option<&'p str>,
+    src_hier: Option<&'p str>,
+    dst_resc: Option<&'p str>,
+    dst_hier: Option<&'p str>,
+    replica_number: Option<u32>,
+    admin: bool,
+}
+
+pub struct PhymvRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    path: &'p Path,
+    opts: PhymvOptions<'p>,
+    recursive: bool,
+}
+
+impl<'conn, 'p, T, C> PhymvRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, path: &'p Path) -> Self {
+        Self {
+            conn,
+            path,
+            opts: PhymvOptions::default(),
+            recursive: false,
+        }
+    }
+
+    /// Move the replica on this resource.
+    pub fn src_resc(mut self, resc: &'p str) -> Self {
+        self.opts.src_resc = Some(resc);
+        self
+    }
+
+    /// Move the replica in this resource hierarchy, e.g., `root;leaf`.
+    pub fn src_hier(mut self, hier: &'p str) -> Self {
+        self.opts.src_hier = Some(hier);
+        self
+    }
+
+    /// Move the replica to this resource.
+    pub fn dst_resc(mut self, resc: &'p str) -> Self {
+        self.opts.dst_resc = Some(resc);
+        self
+    }
+
+    /// Move the replica into this resource hierarchy.
+    pub fn dst_hier(mut self, hier: &'p str) -> Self {
+        self.opts.dst_hier = Some(hier);
+        self
+    }
+
+    /// Move the replica with this number.
+    pub fn replica_number(mut self, replica_number: u32) -> Self {
+        self.opts.replica_number = Some(replica_number);
+        self
+    }
+
+    /// Move replicas owned by other users. Requires a rodsadmin.
+    pub fn admin(mut self, admin: bool) -> Self {
+        self.opts.admin = admin;
+        self
+    }
+
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub async fn execute(self) -> Result<Outcomes, IrodsError> {
+        if self.opts.dst_resc.is_none() && self.opts.dst_hier.is_none() {
+            return Err(IrodsError::Other(
+                "Physical move needs a destination resource".into(),
+            ));
+        }
+
+        let data_objects = self
+            .conn
+            .data_objects_to_process(self.path, self.recursive)
+            .await?;
+
+        let mut outcomes = Vec::with_capacity(data_objects.len());
+
+        for path in data_objects {
+            let result = self.conn.phymv_data_obj(&path, self.opts).await;
+            record_outcome(&mut outcomes, path, result)?;
+        }
+
+        Ok(outcomes)
+    }
+}
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Moves a replica of a data object, or with `recursive` of every data
+    /// object in a collection, to another resource on the server.
+    pub fn phymv<'conn, 'p>(&'conn mut self, path: &'p Path) -> PhymvRequest<'conn, 'p, T, C> {
+        PhymvRequest::new(self, path)
+    }
+
+    fn make_phymv_inp(path: &Path, opts: PhymvOptions) -> DataObjInp {
+        let mut inp = DataObjInp::new(path.to_str().unwrap().to_owned(), OprType::PhyMove, 0, 0);
+
+        if let Some(r) = opts.src_resc {
+            inp.cond_input
+                .add_kw(CondInputKw::RescNameKw, r.to_string());
+        }
+
+        if let Some(h) = opts.src_hier {
+            inp.cond_input
+                .add_kw(CondInputKw::RescHierStrKw, h.to_string());
+        }
+
+        if let Some(r) = opts.dst_resc {
+            inp.cond_input
+                .add_kw(CondInputKw::DestRescNameKw, r.to_string());
+        }
+
+        if let Some(h) = opts.dst_hier {
+            inp.cond_input
+                .add_kw(CondInputKw::DestRescHierStrKw, h.to_string());
+        }
+
+        if let Some(n) = opts.replica_number {
+            inp.cond_input.add_kw(CondInputKw::ReplNumKw, n.to_string());
+        }
+
+        if opts.admin {
+            inp.cond_input.set_kw(CondInputKw::AdminKw);
+        }
+
+        inp
+    }
+
+    async fn phymv_data_obj(
+        &mut self,
+        path: &Path,
+        opts: PhymvOptions<'_>,
+    ) -> Result<(), IrodsError> {
+        let inp = Self::make_phymv_inp(path, opts);
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::DataObjPhymv as i32)
+            .await?;
+
+        self.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+}
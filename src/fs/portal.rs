@@ -0,0 +1,398 @@
+use std::{io::SeekFrom, path::Path};
+
+use futures::future::try_join_all;
+use tokio::{
+    fs::OpenOptions,
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::APN,
+    connection::{ssl::PortalCipher, Connection},
+    error::errors::IrodsError,
+    msg::{header::MsgType, int_msg::IntMsg, portal_opr_out::PortList},
+};
+
+use super::transfer::split_into_slices;
+
+// Operation types of a transfer header
+const PUT_OPR: i32 = 1;
+const DONE_OPR: i32 = 9999;
+
+/// Largest plaintext chunk sent between transfer headers.
+const TRANS_BUF_SZ: usize = 4 * 1024 * 1024;
+
+/// Precedes every run of bytes on a portal socket. Encoded as the server's
+/// `transferHeader_t`: two 32-bit and two 64-bit big-endian integers.
+#[derive(Debug)]
+struct TransferHeader {
+    opr_type: i32,
+    offset: u64,
+    length: u64,
+}
+
+impl TransferHeader {
+    async fn send<S>(&self, socket: &mut S) -> Result<(), IrodsError>
+    where
+        S: AsyncWrite + Unpin,
+    {
+        let mut buf = [0u8; 24];
+
+        buf[0..4].copy_from_slice(&self.opr_type.to_be_bytes());
+        // flags are unused
+        buf[8..16].copy_from_slice(&self.offset.to_be_bytes());
+        buf[16..24].copy_from_slice(&self.length.to_be_bytes());
+
+        socket.write_all(&buf).await?;
+
+        Ok(())
+    }
+
+    async fn recv<S>(socket: &mut S) -> Result<Self, IrodsError>
+    where
+        S: AsyncRead + Unpin,
+    {
+        let mut buf = [0u8; 24];
+        socket.read_exact(&mut buf).await?;
+
+        Ok(Self {
+            opr_type: i32::from_be_bytes(buf[0..4].try_into().unwrap()),
+            offset: u64::from_be_bytes(buf[8..16].try_into().unwrap()),
+            length: u64::from_be_bytes(buf[16..24].try_into().unwrap()),
+        })
+    }
+}
+
+async fn connect(port_list: &PortList) -> Result<TcpStream, IrodsError> {
+    if port_list.host_addr.is_empty() {
+        return Err(IrodsError::Other(
+            "Server did not return a portal host".into(),
+        ));
+    }
+
+    let mut socket = TcpStream::connect((port_list.host_addr.as_str(), port_list.port_num)).await?;
+
+    // The cookie tells the server which transfer the socket belongs to
+    socket.write_all(&port_list.cookie.to_be_bytes()).await?;
+
+    Ok(socket)
+}
+
+// Unlike the cookie and the transfer headers, the size of an encrypted
+// buffer is written as a raw C int (`myWrite(fd, &new_size, sizeof(int))` in
+// rcPortalOpr.cpp), i.e., in host byte order, which is little endian on
+// every platform the server supports
+async fn send_chunk<S>(
+    socket: &mut S,
+    cipher: Option<&PortalCipher>,
+    chunk: &[u8],
+) -> Result<(), IrodsError>
+where
+    S: AsyncWrite + Unpin,
+{
+    match cipher {
+        Some(cipher) => {
+            let payload = cipher.encrypt(chunk)?;
+            socket
+                .write_all(&(payload.len() as i32).to_le_bytes())
+                .await?;
+            socket.write_all(&payload).await?;
+        }
+        None => socket.write_all(chunk).await?,
+    }
+
+    Ok(())
+}
+
+/// Reads at most `max_len` plaintext bytes into `buf` and returns how many.
+async fn recv_chunk<S>(
+    socket: &mut S,
+    cipher: Option<&PortalCipher>,
+    buf: &mut Vec<u8>,
+    max_len: usize,
+) -> Result<usize, IrodsError>
+where
+    S: AsyncRead + Unpin,
+{
+    match cipher {
+        Some(cipher) => {
+            let mut len = [0u8; 4];
+            socket.read_exact(&mut len).await?;
+
+            let len = i32::from_le_bytes(len);
+            if len <= 0 {
+                return Err(IrodsError::Other("Invalid encrypted portal buffer".into()));
+            }
+
+            buf.resize(len as usize, 0);
+            socket.read_exact(buf).await?;
+
+            *buf = cipher.decrypt(buf)?;
+
+            Ok(buf.len())
+        }
+        None => {
+            buf.resize(max_len, 0);
+            socket.read_exact(buf).await?;
+
+            Ok(max_len)
+        }
+    }
+}
+
+async fn put_slice(
+    port_list: &PortList,
+    cipher: Option<&PortalCipher>,
+    local_path: &Path,
+    slice: Option<(usize, usize)>,
+) -> Result<(), IrodsError> {
+    let mut socket = connect(port_list).await?;
+
+    // There can be more sockets than slices for a short file. Those sockets
+    // only need to be told that there is nothing to send.
+    if let Some((offset, len)) = slice {
+        let mut file = OpenOptions::new().read(true).open(local_path).await?;
+        file.seek(SeekFrom::Start(offset as u64)).await?;
+
+        TransferHeader {
+            opr_type: PUT_OPR,
+            offset: offset as u64,
+            length: len as u64,
+        }
+        .send(&mut socket)
+        .await?;
+
+        let mut buf = vec![0; TRANS_BUF_SZ.min(len)];
+        let mut remaining = len;
+
+        while remaining > 0 {
+            let chunk_len = remaining.min(TRANS_BUF_SZ);
+            file.read_exact(&mut buf[..chunk_len]).await?;
+
+            send_chunk(&mut socket, cipher, &buf[..chunk_len]).await?;
+
+            remaining -= chunk_len;
+        }
+    }
+
+    TransferHeader {
+        opr_type: DONE_OPR,
+        offset: 0,
+        length: 0,
+    }
+    .send(&mut socket)
+    .await?;
+
+    socket.flush().await?;
+
+    Ok(())
+}
+
+async fn get_slices(
+    port_list: &PortList,
+    cipher: Option<&PortalCipher>,
+    local_path: &Path,
+) -> Result<(), IrodsError> {
+    let mut socket = connect(port_list).await?;
+    let mut file = OpenOptions::new().write(true).open(local_path).await?;
+
+    let mut buf = Vec::new();
+
+    // The server decides which parts of the object each socket carries
+    loop {
+        let header = TransferHeader::recv(&mut socket).await?;
+
+        if header.opr_type == DONE_OPR {
+            break;
+        }
+
+        file.seek(SeekFrom::Start(header.offset)).await?;
+
+        let mut remaining = header.length as usize;
+
+        while remaining > 0 {
+            let len =
+                recv_chunk(&mut socket, cipher, &mut buf, remaining.min(TRANS_BUF_SZ)).await?;
+
+            if len > remaining {
+                return Err(IrodsError::Other(
+                    "Portal sent more data than announced".into(),
+                ));
+            }
+
+            file.write_all(&buf[..len]).await?;
+            remaining -= len;
+        }
+    }
+
+    file.flush().await?;
+
+    Ok(())
+}
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Sends `local_path` over `num_threads` portal sockets. The server
+    /// has already opened the data object.
+    pub(crate) async fn put_to_portal(
+        &mut self,
+        port_list: &PortList,
+        num_threads: usize,
+        l1_desc_inx: i32,
+        local_path: &Path,
+        size: usize,
+    ) -> Result<(), IrodsError> {
+        let cipher = self.resources.portal_cipher.clone();
+        let slices = split_into_slices(size, num_threads as u32);
+
+        let result = try_join_all((0..num_threads).map(|thread| {
+            put_slice(
+                port_list,
+                cipher.as_ref(),
+                local_path,
+                slices.get(thread).copied(),
+            )
+        }))
+        .await;
+
+        let completed = self.complete_portal_opr(l1_desc_inx, result.is_ok()).await;
+
+        result?;
+        completed
+    }
+
+    /// Receives the data object into `local_path` over `num_threads`
+    /// portal sockets.
+    pub(crate) async fn get_from_portal(
+        &mut self,
+        port_list: &PortList,
+        num_threads: usize,
+        l1_desc_inx: i32,
+        local_path: &Path,
+    ) -> Result<(), IrodsError> {
+        let cipher = self.resources.portal_cipher.clone();
+
+        let result = try_join_all(
+            (0..num_threads).map(|_| get_slices(port_list, cipher.as_ref(), local_path)),
+        )
+        .await;
+
+        let completed = self.complete_portal_opr(l1_desc_inx, result.is_ok()).await;
+
+        result?;
+        completed
+    }
+
+    /// Writes `local_path` through the descriptor the server opened, for
+    /// files it did not think large enough for a portal.
+    pub(crate) async fn put_through_descriptor(
+        &mut self,
+        l1_desc_inx: i32,
+        local_path: &Path,
+    ) -> Result<(), IrodsError> {
+        let result = self.write_file_to_descriptor(l1_desc_inx, local_path).await;
+
+        let completed = self.complete_portal_opr(l1_desc_inx, result.is_ok()).await;
+
+        result?;
+        completed
+    }
+
+    async fn write_file_to_descriptor(
+        &mut self,
+        l1_desc_inx: i32,
+        local_path: &Path,
+    ) -> Result<(), IrodsError> {
+        let mut file = OpenOptions::new().read(true).open(local_path).await?;
+
+        if self.resources.bytes_buf.len() < TRANS_BUF_SZ {
+            self.resources.bytes_buf.resize(TRANS_BUF_SZ, 0);
+        }
+
+        loop {
+            let len = file
+                .read(&mut self.resources.bytes_buf[..TRANS_BUF_SZ])
+                .await?;
+
+            if len == 0 {
+                return Ok(());
+            }
+
+            self.write_data_obj_from_bytes_buf(l1_desc_inx, len).await?;
+        }
+    }
+
+    // Lets the server close the data object once every socket is done. A
+    // negative descriptor tells it the transfer failed.
+    pub(crate) async fn complete_portal_opr(
+        &mut self,
+        l1_desc_inx: i32,
+        succeeded: bool,
+    ) -> Result<(), IrodsError> {
+        let inp = IntMsg::new(if succeeded { l1_desc_inx } else { -1 });
+
+        self.resources
+            .send_header_then_msg::<T, _>(&inp, MsgType::RodsApiReq, APN::OprComplete as i32)
+            .await?;
+
+        self.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::io::duplex;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn encrypted_chunk_size_is_little_endian() {
+        let cipher = PortalCipher::new("AES-256-CBC", vec![7; 32]);
+        let (mut client, mut server) = duplex(1 << 16);
+
+        send_chunk(&mut client, Some(&cipher), b"portal data")
+            .await
+            .unwrap();
+
+        // A 32-byte IV followed by one padded block
+        let mut len = [0u8; 4];
+        server.read_exact(&mut len).await.unwrap();
+        assert_eq!(len, [48, 0, 0, 0]);
+
+        let mut payload = vec![0; 48];
+        server.read_exact(&mut payload).await.unwrap();
+        assert_eq!(cipher.decrypt(&payload).unwrap(), b"portal data");
+    }
+
+    #[tokio::test]
+    async fn encrypted_chunk_round_trip() {
+        let cipher = PortalCipher::new("AES-128-CBC", vec![3; 16]);
+        let (mut client, mut server) = duplex(1 << 16);
+
+        send_chunk(&mut client, Some(&cipher), b"first")
+            .await
+            .unwrap();
+        send_chunk(&mut client, Some(&cipher), b"second")
+            .await
+            .unwrap();
+
+        let mut buf = Vec::new();
+
+        let len = recv_chunk(&mut server, Some(&cipher), &mut buf, 0)
+            .await
+            .unwrap();
+        assert_eq!(&buf[..len], b"first");
+
+        let len = recv_chunk(&mut server, Some(&cipher), &mut buf, 0)
+            .await
+            .unwrap();
+        assert_eq!(&buf[..len], b"second");
+    }
+}
@@ -0,0 +1,269 @@
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use md5::{Digest, Md5};
+use sha2::Sha256;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{data_obj_inp::DataObjInp, header::MsgType, portal_opr_out::PortalOprOut},
+    ChecksumAlgo,
+};
+
+use super::{OpenFlag, OprType};
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Writes a whole data object in a single request. Meant for objects
+    /// small enough that the server accepts them in one buffer.
+    pub fn put_request<'conn, 'p>(&'conn mut self, path: &'p Path) -> PutRequest<'conn, 'p, T, C> {
+        PutRequest::new(self, path)
+    }
+
+    fn make_put_data_obj_inp(
+        path: &Path,
+        size: usize,
+        num_threads: u32,
+        resc: Option<&str>,
+        force: bool,
+        checksum: bool,
+        verify_checksum: Option<String>,
+    ) -> DataObjInp {
+        let mut inp = DataObjInp::new(
+            path.to_str().unwrap().to_owned(),
+            OprType::Put,
+            OpenFlag::ReadWrite as i32,
+            0o644,
+        );
+        inp.data_size = size as i64;
+        inp.num_threads = num_threads as i32;
+
+        if let Some(r) = resc {
+            inp.cond_input
+                .add_kw(CondInputKw::DestRescNameKw, r.to_string());
+        }
+
+        if force {
+            inp.cond_input.set_kw(CondInputKw::ForceFlagKw);
+        }
+
+        if checksum {
+            inp.cond_input.set_kw(CondInputKw::RegChksumKw);
+        }
+
+        // The server compares this against the checksum it computes
+        if let Some(chksum) = verify_checksum {
+            inp.cond_input.add_kw(CondInputKw::VerifyChksumKw, chksum);
+        }
+
+        inp
+    }
+}
+
+pub struct PutRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    path: &'p Path,
+    resc: Option<&'p str>,
+    force: bool,
+    checksum: bool,
+    verify_checksum: bool,
+    checksum_algo: ChecksumAlgo,
+    num_threads: u32,
+}
+
+impl<'conn, 'p, T, C> PutRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, path: &'p Path) -> Self {
+        Self {
+            conn,
+            path,
+            resc: None,
+            force: false,
+            checksum: false,
+            verify_checksum: false,
+            checksum_algo: ChecksumAlgo::MD5,
+            num_threads: 0,
+        }
+    }
+
+    pub fn set_resc(mut self, resc: &'p str) -> Self {
+        self.resc = Some(resc);
+        self
+    }
+
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Have the server compute and register a checksum.
+    pub fn checksum(mut self, checksum: bool) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Have the server verify the data against a checksum computed here.
+    pub fn verify_checksum(mut self, verify_checksum: bool) -> Self {
+        self.verify_checksum = verify_checksum;
+        self
+    }
+
+    /// Algorithm of the checksum sent for verification. It must match the
+    /// server's `default_hash_scheme`, MD5 unless configured otherwise.
+    /// Only MD5 and SHA-256 are supported.
+    pub fn checksum_algo(mut self, checksum_algo: ChecksumAlgo) -> Self {
+        self.checksum_algo = checksum_algo;
+        self
+    }
+
+    /// Number of portal sockets to ask for when sending a file. Zero leaves
+    /// the choice to the server.
+    pub fn num_threads(mut self, num_threads: u32) -> Self {
+        self.num_threads = num_threads;
+        self
+    }
+
+    pub async fn execute(self, data: &[u8]) -> Result<(), IrodsError> {
+        let verify_checksum = match self.verify_checksum {
+            true => Some(checksum_of(self.checksum_algo, data)?),
+            false => None,
+        };
+
+        let inp = Connection::<T, C>::make_put_data_obj_inp(
+            self.path,
+            data.len(),
+            0,
+            self.resc,
+            self.force,
+            self.checksum,
+            verify_checksum,
+        );
+
+        self.conn
+            .resources
+            .send_header_then_msg_and_bytes::<T, _>(
+                &inp,
+                MsgType::RodsApiReq,
+                APN::DataObjPut as i32,
+                data,
+            )
+            .await?;
+
+        self.conn.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+
+    /// Sends the contents of `local_path` through the server's portal, the
+    /// way the iRODS clients transfer large files. The data travels over
+    /// separate sockets, encrypted when the connection uses SSL.
+    pub async fn execute_from_file(self, local_path: &Path) -> Result<(), IrodsError> {
+        let size = tokio::fs::metadata(local_path).await?.len() as usize;
+
+        let verify_checksum = match self.verify_checksum {
+            true => Some(checksum_of_file(self.checksum_algo, local_path.to_path_buf()).await?),
+            false => None,
+        };
+
+        let inp = Connection::<T, C>::make_put_data_obj_inp(
+            self.path,
+            size,
+            self.num_threads,
+            self.resc,
+            self.force,
+            self.checksum,
+            verify_checksum,
+        );
+
+        self.conn
+            .resources
+            .send_header_then_msg::<T, _>(&inp, MsgType::RodsApiReq, APN::DataObjPut as i32)
+            .await?;
+
+        let (_, out) = self
+            .conn
+            .resources
+            .get_header_msg_and_bytes::<T, PortalOprOut>(&mut Vec::new())
+            .await?;
+
+        // Files under the server's parallel threshold get no portal
+        if out.num_threads <= 0 {
+            return self
+                .conn
+                .put_through_descriptor(out.l1_desc_inx, local_path)
+                .await;
+        }
+
+        self.conn
+            .put_to_portal(
+                &out.port_list,
+                out.num_threads as usize,
+                out.l1_desc_inx,
+                local_path,
+                size,
+            )
+            .await
+    }
+}
+
+/// Formats the checksum of `data` the way the server records it: MD5 as a
+/// hex digest and SHA-256 as `sha2:` followed by the base64 digest.
+fn checksum_of(algo: ChecksumAlgo, mut data: impl std::io::Read) -> Result<String, IrodsError> {
+    match algo {
+        ChecksumAlgo::MD5 => {
+            let mut digest = Md5::new();
+            std::io::copy(&mut data, &mut digest)?;
+
+            Ok(faster_hex::hex_string(&digest.finalize()))
+        }
+        ChecksumAlgo::SHA256 => {
+            let mut digest = Sha256::new();
+            std::io::copy(&mut data, &mut digest)?;
+
+            Ok(format!(
+                "sha2:{}",
+                base64::engine::general_purpose::STANDARD.encode(digest.finalize())
+            ))
+        }
+        _ => Err(IrodsError::Other(
+            "Only MD5 and SHA-256 checksums can be verified".into(),
+        )),
+    }
+}
+
+async fn checksum_of_file(algo: ChecksumAlgo, path: PathBuf) -> Result<String, IrodsError> {
+    tokio::task::spawn_blocking(move || checksum_of(algo, std::fs::File::open(path)?))
+        .await
+        .map_err(|_| IrodsError::Other("Failed to checksum file".to_string()))?
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checksum_formats() {
+        assert_eq!(
+            checksum_of(ChecksumAlgo::MD5, &b"hello"[..]).unwrap(),
+            "5d41402abc4b2a76b9719d911017c592"
+        );
+        assert_eq!(
+            checksum_of(ChecksumAlgo::SHA256, &b"hello"[..]).unwrap(),
+            "sha2:LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ="
+        );
+        assert!(checksum_of(ChecksumAlgo::SHA1, &b"hello"[..]).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{data_obj_inp::DataObjInp, header::MsgType, str_msg::StrMsg},
+};
+
+use super::{OpenFlag, OprType};
+
+/// Returned in place of a host name when the server already connected to
+/// is the best one to talk to.
+const THIS_ADDRESS: &str = "thisAddress";
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    fn make_get_host_inp(
+        path: &Path,
+        opr_type: OprType,
+        resc: Option<(CondInputKw, &str)>,
+    ) -> DataObjInp {
+        let mut inp = DataObjInp::new(
+            path.to_str().unwrap().to_owned(),
+            opr_type,
+            OpenFlag::ReadOnly as i32,
+            0,
+        );
+
+        if let Some((kw, r)) = resc {
+            inp.cond_input.add_kw(kw, r.to_string());
+        }
+
+        inp
+    }
+
+    async fn get_host(&mut self, inp: DataObjInp, apn: APN) -> Result<Option<String>, IrodsError> {
+        self.resources
+            .send_header_then_msg::<T, _>(&inp, MsgType::RodsApiReq, apn as i32)
+            .await?;
+
+        let (_, out) = self.resources.get_header_and_msg::<T, StrMsg>().await?;
+
+        if out.my_str.is_empty() || out.my_str == THIS_ADDRESS {
+            Ok(None)
+        } else {
+            Ok(Some(out.my_str))
+        }
+    }
+
+    /// Asks which resource server holds the replica that a read of `path`
+    /// would be served from. `None` means this server should be used.
+    pub async fn get_host_for_get(
+        &mut self,
+        path: &Path,
+        resc: Option<&str>,
+    ) -> Result<Option<String>, IrodsError> {
+        let inp = Self::make_get_host_inp(
+            path,
+            OprType::Get,
+            resc.map(|r| (CondInputKw::RescNameKw, r)),
+        );
+
+        self.get_host(inp, APN::GetHostForGet).await
+    }
+
+    /// Asks which resource server a write of `path` would land on. `None`
+    /// means this server should be used.
+    pub async fn get_host_for_put(
+        &mut self,
+        path: &Path,
+        resc: Option<&str>,
+    ) -> Result<Option<String>, IrodsError> {
+        let inp = Self::make_get_host_inp(
+            path,
+            OprType::Put,
+            resc.map(|r| (CondInputKw::DestRescNameKw, r)),
+        );
+
+        self.get_host(inp, APN::GetHostForPut).await
+    }
+}
@@ -0,0 +1,298 @@
+use std::path::Path;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, ObjectType, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{coll::CollInp, data_obj_inp::DataObjInp, header::MsgType},
+};
+
+use super::{
+    resc::{record_outcome, Outcomes},
+    OprType,
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+struct RegisterOptions<'p> {
+    resc: Option<&'p str>,
+    force: bool,
+    checksum: bool,
+    verify_checksum: bool,
+    replica: bool,
+    recursive: bool,
+    collection_type: Option<&'p str>,
+}
+
+pub struct RegisterRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    physical_path: &'p Path,
+    logical_path: &'p Path,
+    opts: RegisterOptions<'p>,
+}
+
+impl<'conn, 'p, T, C> RegisterRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(
+        conn: &'conn mut Connection<T, C>,
+        physical_path: &'p Path,
+        logical_path: &'p Path,
+    ) -> Self {
+        Self {
+            conn,
+            physical_path,
+            logical_path,
+            opts: RegisterOptions::default(),
+        }
+    }
+
+    /// The resource whose vault holds the physical path.
+    pub fn set_resc(mut self, resc: &'p str) -> Self {
+        self.opts.resc = Some(resc);
+        self
+    }
+
+    pub fn force(mut self, force: bool) -> Self {
+        self.opts.force = force;
+        self
+    }
+
+    /// Have the server compute and register a checksum.
+    pub fn checksum(mut self, checksum: bool) -> Self {
+        self.opts.checksum = checksum;
+        self
+    }
+
+    /// Have the server compute a checksum and compare it to the one already
+    /// registered.
+    pub fn verify_checksum(mut self, verify_checksum: bool) -> Self {
+        self.opts.verify_checksum = verify_checksum;
+        self
+    }
+
+    /// Register the file as another replica of an existing data object.
+    pub fn replica(mut self, replica: bool) -> Self {
+        self.opts.replica = replica;
+        self
+    }
+
+    /// Register a directory and everything under it.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.opts.recursive = recursive;
+        self
+    }
+
+    /// Mount a directory as a collection. Its contents are not registered
+    /// but read from the directory whenever the collection is accessed.
+    pub fn mount(mut self, mount: bool) -> Self {
+        self.opts.collection_type = mount.then_some("mountPoint");
+        self
+    }
+
+    // Registers the path as a special collection of this catalog type
+    pub(crate) fn collection_type(mut self, collection_type: &'p str) -> Self {
+        self.opts.collection_type = Some(collection_type);
+        self
+    }
+
+    pub async fn execute(self) -> Result<(), IrodsError> {
+        self.conn
+            .register_path(self.physical_path, self.logical_path, self.opts)
+            .await
+    }
+}
+
+pub struct UnregisterRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    path: &'p Path,
+    admin: bool,
+    recursive: bool,
+}
+
+impl<'conn, 'p, T, C> UnregisterRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, path: &'p Path) -> Self {
+        Self {
+            conn,
+            path,
+            admin: false,
+            recursive: false,
+        }
+    }
+
+    /// Unregister data objects owned by other users. Requires a rodsadmin.
+    pub fn admin(mut self, admin: bool) -> Self {
+        self.admin = admin;
+        self
+    }
+
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Unregisters every data object, then, if all of them succeeded, drops
+    /// the now empty collections from the catalog.
+    pub async fn execute(self) -> Result<Outcomes, IrodsError> {
+        let data_objects = self
+            .conn
+            .data_objects_to_process(self.path, self.recursive)
+            .await?;
+
+        let mut outcomes = Vec::with_capacity(data_objects.len());
+
+        for path in data_objects {
+            let result = self.conn.unregister_data_obj(&path, self.admin).await;
+            record_outcome(&mut outcomes, path, result)?;
+        }
+
+        if outcomes.iter().all(|(_, result)| result.is_ok()) {
+            let stat = self.conn.stat(self.path).await?;
+
+            if let ObjectType::Coll = stat.object_type {
+                self.conn.unregister_empty_colls(self.path).await?;
+            }
+        }
+
+        Ok(outcomes)
+    }
+}
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Registers a file or directory already in a resource's vault at
+    /// `logical_path`, without copying its data.
+    pub fn register<'conn, 'p>(
+        &'conn mut self,
+        physical_path: &'p Path,
+        logical_path: &'p Path,
+    ) -> RegisterRequest<'conn, 'p, T, C> {
+        RegisterRequest::new(self, physical_path, logical_path)
+    }
+
+    /// Removes data objects, or with `recursive` a collection, from the
+    /// catalog while leaving their physical files in place.
+    pub fn unregister<'conn, 'p>(
+        &'conn mut self,
+        path: &'p Path,
+    ) -> UnregisterRequest<'conn, 'p, T, C> {
+        UnregisterRequest::new(self, path)
+    }
+
+    // Removes the collections under `path`, deepest first, one at a time
+    // and without the recursive flag, so that anything the listing missed
+    // makes the server refuse rather than delete physical files
+    async fn unregister_empty_colls(&mut self, path: &Path) -> Result<(), IrodsError> {
+        let mut colls = vec![path.to_path_buf()];
+        let mut next = 0;
+
+        while next < colls.len() {
+            let (_, sub_collections) = self.list_children(&colls[next]).await?;
+            colls.extend(sub_collections);
+            next += 1;
+        }
+
+        for coll in colls.iter().rev() {
+            let mut inp = CollInp::builder().build();
+            inp.name = coll.to_str().unwrap().to_owned();
+
+            self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::RmColl as i32)
+                .await?;
+
+            self.resources.read_coll_opr_status::<T>().await?;
+        }
+
+        Ok(())
+    }
+
+    fn make_register_inp(
+        physical_path: &Path,
+        logical_path: &Path,
+        opts: RegisterOptions,
+    ) -> DataObjInp {
+        let mut inp = DataObjInp::new(logical_path.to_str().unwrap().to_owned(), OprType::No, 0, 0);
+
+        inp.cond_input.add_kw(
+            CondInputKw::FilePathKw,
+            physical_path.to_str().unwrap().to_owned(),
+        );
+
+        if let Some(r) = opts.resc {
+            inp.cond_input
+                .add_kw(CondInputKw::DestRescNameKw, r.to_string());
+        }
+
+        if opts.force {
+            inp.cond_input.set_kw(CondInputKw::ForceFlagKw);
+        }
+
+        if opts.checksum {
+            inp.cond_input.set_kw(CondInputKw::RegChksumKw);
+        }
+
+        if opts.verify_checksum {
+            inp.cond_input.set_kw(CondInputKw::VerifyChksumKw);
+        }
+
+        if opts.replica {
+            inp.cond_input.set_kw(CondInputKw::RegReplKw);
+        }
+
+        if let Some(collection_type) = opts.collection_type {
+            inp.cond_input
+                .add_kw(CondInputKw::CollectionTypeKw, collection_type.to_string());
+        } else if opts.recursive {
+            inp.cond_input.set_kw(CondInputKw::CollectionKw);
+        }
+
+        inp
+    }
+
+    async fn register_path(
+        &mut self,
+        physical_path: &Path,
+        logical_path: &Path,
+        opts: RegisterOptions<'_>,
+    ) -> Result<(), IrodsError> {
+        let inp = Self::make_register_inp(physical_path, logical_path, opts);
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::PhyPathReg as i32)
+            .await?;
+
+        self.resources.read_coll_opr_status::<T>().await?;
+
+        Ok(())
+    }
+
+    async fn unregister_data_obj(&mut self, path: &Path, admin: bool) -> Result<(), IrodsError> {
+        let mut inp = DataObjInp::new(path.to_str().unwrap().to_owned(), OprType::Unreg, 0, 0);
+
+        if admin {
+            inp.cond_input.set_kw(CondInputKw::AdminKw);
+        }
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::DataObjUnlink as i32)
+            .await?;
+
+        self.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    ffi::OsStr,
+    path::{Component, Path},
+};
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{ObjectType, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{data_obj_copy_inp::DataObjCopyInp, data_obj_inp::DataObjInp, header::MsgType},
+};
+
+use super::OprType;
+
+// The zone is the first component of an absolute logical path
+fn zone_of(path: &Path) -> Option<&OsStr> {
+    path.components().find_map(|c| match c {
+        Component::Normal(zone) => Some(zone),
+        _ => None,
+    })
+}
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    fn make_rename_inp(src: &Path, dst: &Path, opr_type: OprType) -> DataObjCopyInp {
+        DataObjCopyInp::new(
+            DataObjInp::new(src.to_str().unwrap().to_owned(), opr_type, 0, 0),
+            DataObjInp::new(dst.to_str().unwrap().to_owned(), opr_type, 0, 0),
+        )
+    }
+
+    /// Renames a data object or collection, like `mv`. If `dst` is an
+    /// existing collection, `src` is moved into it under its current name.
+    /// Both paths must be in the same zone.
+    pub async fn rename(&mut self, src: &Path, dst: &Path) -> Result<(), IrodsError> {
+        if zone_of(src) != zone_of(dst) {
+            return Err(IrodsError::Other(format!(
+                "Cannot move [{}] to [{}]: paths are in different zones",
+                src.display(),
+                dst.display()
+            )));
+        }
+
+        let opr_type = match self.try_stat(src).await? {
+            Some(stat) => match stat.object_type {
+                ObjectType::DataObj => OprType::RenameDataObj,
+                ObjectType::Coll => OprType::RenameColl,
+                _ => OprType::RenameUnknownType,
+            },
+            None => {
+                return Err(IrodsError::Other(format!(
+                    "No such path [{}]",
+                    src.display()
+                )))
+            }
+        };
+
+        let dst = match self.try_stat(dst).await? {
+            Some(stat) if matches!(stat.object_type, ObjectType::Coll) => dst
+                .join(src.file_name().ok_or_else(|| {
+                    IrodsError::Other(format!("Cannot move [{}]", src.display()))
+                })?),
+            Some(_) => {
+                return Err(IrodsError::Other(format!(
+                    "Destination [{}] already exists",
+                    dst.display()
+                )))
+            }
+            None => dst.to_path_buf(),
+        };
+
+        if matches!(opr_type, OprType::RenameColl) && dst.starts_with(src) {
+            return Err(IrodsError::Other(format!(
+                "Cannot move [{}] into itself",
+                src.display()
+            )));
+        }
+
+        let inp = Self::make_rename_inp(src, &dst, opr_type);
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::DataObjRename as i32)
+            .await?;
+
+        self.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn zone_is_first_component() {
+        assert_eq!(
+            zone_of(Path::new("/tempZone/home/rods")),
+            Some(OsStr::new("tempZone"))
+        );
+        assert_ne!(
+            zone_of(Path::new("/tempZone/home/rods")),
+            zone_of(Path::new("/otherZone/home/rods"))
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::APN,
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{bytes_buf::BytesBuf, header::MsgType},
+};
+
+use super::DataObjectHandle;
+
+/// What the server knows about an open data object. The replica token lets
+/// other connections open the same replica for writing.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileDescriptorInfo {
+    pub replica_token: String,
+    pub data_object_info: DataObjectInfo,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DataObjectInfo {
+    pub replica_number: i64,
+    pub resource_hierarchy: String,
+}
+
+#[derive(Serialize)]
+struct ReplicaCloseInp {
+    fd: DataObjectHandle,
+    update_size: bool,
+    update_status: bool,
+    compute_checksum: bool,
+    send_notifications: bool,
+    preserve_replica_state_table: bool,
+}
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub async fn get_file_descriptor_info(
+        &mut self,
+        handle: DataObjectHandle,
+    ) -> Result<FileDescriptorInfo, IrodsError> {
+        let inp = BytesBuf::new(&format!(r#"{{"fd":{handle}}}"#));
+
+        self.resources
+            .send_header_then_msg::<T, _>(
+                &inp,
+                MsgType::RodsApiReq,
+                APN::GetFileDescriptorInfo as i32,
+            )
+            .await?;
+
+        let (_, out) = self.resources.get_header_and_msg::<T, BytesBuf>().await?;
+
+        Ok(serde_json::from_str(&out.buf)?)
+    }
+
+    pub fn replica_close_request(&mut self, handle: DataObjectHandle) -> ReplicaCloseRequest<T, C> {
+        ReplicaCloseRequest::new(self, handle)
+    }
+}
+
+/// Closes a handle opened with or sharing a replica token. Only the last
+/// handle to close should update the catalog.
+pub struct ReplicaCloseRequest<'conn, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    inp: ReplicaCloseInp,
+}
+
+impl<'conn, T, C> ReplicaCloseRequest<'conn, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, handle: DataObjectHandle) -> Self {
+        Self {
+            conn,
+            inp: ReplicaCloseInp {
+                fd: handle,
+                update_size: true,
+                update_status: true,
+                compute_checksum: false,
+                send_notifications: true,
+                preserve_replica_state_table: false,
+            },
+        }
+    }
+
+    pub fn update_size(mut self, update_size: bool) -> Self {
+        self.inp.update_size = update_size;
+        self
+    }
+
+    pub fn update_status(mut self, update_status: bool) -> Self {
+        self.inp.update_status = update_status;
+        self
+    }
+
+    pub fn compute_checksum(mut self, compute_checksum: bool) -> Self {
+        self.inp.compute_checksum = compute_checksum;
+        self
+    }
+
+    pub fn send_notifications(mut self, send_notifications: bool) -> Self {
+        self.inp.send_notifications = send_notifications;
+        self
+    }
+
+    pub fn preserve_replica_state_table(mut self, preserve: bool) -> Self {
+        self.inp.preserve_replica_state_table = preserve;
+        self
+    }
+
+    /// Leaves the catalog alone, for every handle but the last.
+    pub fn secondary(self) -> Self {
+        self.update_size(false)
+            .update_status(false)
+            .compute_checksum(false)
+            .send_notifications(false)
+    }
+
+    pub async fn execute(self) -> Result<(), IrodsError> {
+        let inp = BytesBuf::new(&serde_json::to_string(&self.inp)?);
+
+        self.conn
+            .resources
+            .send_header_then_msg::<T, _>(&inp, MsgType::RodsApiReq, APN::ReplicaClose as i32)
+            .await?;
+
+        self.conn.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+}
@@ -1,20 +1,302 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::{bosd::ProtocolEncoding, connection::Connection, error::errors::IrodsError};
+use crate::{
+    bosd::ProtocolEncoding,
+    common::{cond_input_kw::CondInputKw, ObjectType, APN},
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{data_obj_inp::DataObjInp, header::MsgType},
+};
+
+use super::OprType;
+
+/// The result of an operation on each data object it touched.
+pub type Outcomes = Vec<(PathBuf, Result<(), IrodsError>)>;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ReplOptions<'p> {
+    src_resc: Option<&'p str>,
+    dst_resc: Option<&'p str>,
+    update_stale: bool,
+    all_resources: bool,
+}
+
+pub struct ReplRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    path: &'p Path,
+    opts: ReplOptions<'p>,
+    recursive: bool,
+}
+
+impl<'conn, 'p, T, C> ReplRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, path: &'p Path) -> Self {
+        Self {
+            conn,
+            path,
+            opts: ReplOptions::default(),
+            recursive: false,
+        }
+    }
+
+    /// Replicate from the replica on this resource.
+    pub fn src_resc(mut self, resc: &'p str) -> Self {
+        self.opts.src_resc = Some(resc);
+        self
+    }
+
+    /// Create the new replica on this resource.
+    pub fn dst_resc(mut self, resc: &'p str) -> Self {
+        self.opts.dst_resc = Some(resc);
+        self
+    }
+
+    /// Bring every stale replica up to date instead of creating a new one.
+    pub fn update_stale(mut self, update_stale: bool) -> Self {
+        self.opts.update_stale = update_stale;
+        self
+    }
+
+    /// Replicate to every resource that does not yet hold a replica.
+    pub fn all_resources(mut self, all_resources: bool) -> Self {
+        self.opts.all_resources = all_resources;
+        self
+    }
+
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub async fn execute(self) -> Result<Outcomes, IrodsError> {
+        let data_objects = self
+            .conn
+            .data_objects_to_process(self.path, self.recursive)
+            .await?;
+
+        let mut outcomes = Vec::with_capacity(data_objects.len());
+
+        for path in data_objects {
+            let result = self.conn.repl_data_obj(&path, self.opts).await;
+            record_outcome(&mut outcomes, path, result)?;
+        }
+
+        Ok(outcomes)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct TrimOptions<'p> {
+    resc: Option<&'p str>,
+    replica_number: Option<u32>,
+    min_copies: Option<u32>,
+    min_age_minutes: Option<u32>,
+}
+
+pub struct TrimRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    path: &'p Path,
+    opts: TrimOptions<'p>,
+    recursive: bool,
+}
+
+impl<'conn, 'p, T, C> TrimRequest<'conn, 'p, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>, path: &'p Path) -> Self {
+        Self {
+            conn,
+            path,
+            opts: TrimOptions::default(),
+            recursive: false,
+        }
+    }
+
+    /// Only trim the replica on this resource.
+    pub fn set_resc(mut self, resc: &'p str) -> Self {
+        self.opts.resc = Some(resc);
+        self
+    }
+
+    /// Only trim the replica with this number.
+    pub fn replica_number(mut self, replica_number: u32) -> Self {
+        self.opts.replica_number = Some(replica_number);
+        self
+    }
+
+    /// Keep at least this many good replicas. The server defaults to two.
+    pub fn min_copies(mut self, min_copies: u32) -> Self {
+        self.opts.min_copies = Some(min_copies);
+        self
+    }
+
+    /// Only trim replicas last modified at least this many minutes ago.
+    pub fn min_age_minutes(mut self, min_age_minutes: u32) -> Self {
+        self.opts.min_age_minutes = Some(min_age_minutes);
+        self
+    }
+
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub async fn execute(self) -> Result<Outcomes, IrodsError> {
+        let data_objects = self
+            .conn
+            .data_objects_to_process(self.path, self.recursive)
+            .await?;
+
+        let mut outcomes = Vec::with_capacity(data_objects.len());
+
+        for path in data_objects {
+            let result = self.conn.trim_data_obj(&path, self.opts).await;
+            record_outcome(&mut outcomes, path, result)?;
+        }
+
+        Ok(outcomes)
+    }
+}
+
+// A server error only concerns the one data object, but anything else, e.g.,
+// a broken connection, means the rest would fail too
+pub(crate) fn record_outcome(
+    outcomes: &mut Outcomes,
+    path: PathBuf,
+    result: Result<(), IrodsError>,
+) -> Result<(), IrodsError> {
+    match result {
+        Err(e @ IrodsError::Server(_)) => outcomes.push((path, Err(e))),
+        Err(e) => return Err(e),
+        Ok(()) => outcomes.push((path, Ok(()))),
+    }
+
+    Ok(())
+}
 
 impl<T, C> Connection<T, C>
 where
     T: ProtocolEncoding,
     C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
 {
-    /// Should only be called on a data object
-    pub async fn trim(&mut self, path: &Path) -> Result<(), IrodsError> {
-        todo!()
+    /// Removes replicas of a data object, or with `recursive` of every data
+    /// object in a collection.
+    pub fn trim<'conn, 'p>(&'conn mut self, path: &'p Path) -> TrimRequest<'conn, 'p, T, C> {
+        TrimRequest::new(self, path)
     }
 
-    /// Should only be called on a data object
-    pub async fn repl(&mut self, path: &Path) -> Result<(), IrodsError> {
-        todo!()
+    /// Replicates a data object, or with `recursive` every data object in a
+    /// collection.
+    pub fn repl<'conn, 'p>(&'conn mut self, path: &'p Path) -> ReplRequest<'conn, 'p, T, C> {
+        ReplRequest::new(self, path)
+    }
+
+    pub(crate) async fn data_objects_to_process(
+        &mut self,
+        path: &Path,
+        recursive: bool,
+    ) -> Result<Vec<PathBuf>, IrodsError> {
+        let stat = self.stat(path).await?;
+
+        match stat.object_type {
+            ObjectType::DataObj => Ok(vec![path.to_path_buf()]),
+            ObjectType::Coll if recursive => self.list_data_objects_recursive(path).await,
+            ObjectType::Coll => Err(IrodsError::Other(
+                "Path is a collection and recursive flag not set".into(),
+            )),
+            _ => Err(IrodsError::Other("No such path".to_string())),
+        }
+    }
+
+    fn make_repl_inp(path: &Path, opts: ReplOptions) -> DataObjInp {
+        let mut inp = DataObjInp::new(path.to_str().unwrap().to_owned(), OprType::Replicate, 0, 0);
+
+        if let Some(r) = opts.src_resc {
+            inp.cond_input
+                .add_kw(CondInputKw::RescNameKw, r.to_string());
+        }
+
+        if let Some(r) = opts.dst_resc {
+            inp.cond_input
+                .add_kw(CondInputKw::DestRescNameKw, r.to_string());
+        }
+
+        if opts.update_stale {
+            inp.cond_input.set_kw(CondInputKw::UpdateReplKw);
+        }
+
+        if opts.all_resources {
+            inp.cond_input.set_kw(CondInputKw::AllKw);
+        }
+
+        inp
+    }
+
+    async fn repl_data_obj(
+        &mut self,
+        path: &Path,
+        opts: ReplOptions<'_>,
+    ) -> Result<(), IrodsError> {
+        let inp = Self::make_repl_inp(path, opts);
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::DataObjRepl as i32)
+            .await?;
+
+        // The transfer stats that come with the status are of no interest
+        self.resources.read_status::<T>().await?;
+
+        Ok(())
+    }
+
+    fn make_trim_inp(path: &Path, opts: TrimOptions) -> DataObjInp {
+        let mut inp = DataObjInp::new(path.to_str().unwrap().to_owned(), OprType::No, 0, 0);
+
+        if let Some(r) = opts.resc {
+            inp.cond_input
+                .add_kw(CondInputKw::RescNameKw, r.to_string());
+        }
+
+        if let Some(n) = opts.replica_number {
+            inp.cond_input.add_kw(CondInputKw::ReplNumKw, n.to_string());
+        }
+
+        if let Some(n) = opts.min_copies {
+            inp.cond_input.add_kw(CondInputKw::CopiesKw, n.to_string());
+        }
+
+        if let Some(age) = opts.min_age_minutes {
+            inp.cond_input.add_kw(CondInputKw::AgeKw, age.to_string());
+        }
+
+        inp
+    }
+
+    async fn trim_data_obj(
+        &mut self,
+        path: &Path,
+        opts: TrimOptions<'_>,
+    ) -> Result<(), IrodsError> {
+        let inp = Self::make_trim_inp(path, opts);
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::DataObjTrim as i32)
+            .await?;
+
+        self.resources.read_status::<T>().await?;
+
+        Ok(())
     }
 
     pub async fn add_child_resc(
@@ -0,0 +1,204 @@
+use std::path::{Path, PathBuf};
+
+use async_stream::try_stream;
+use futures::Stream;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::icat_column::IcatColumn,
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::gen_query::{GenQueryInp, IcatPredicate, QueryBuilder},
+    AVUColumns, AVUTarget, Collection, DataObject,
+};
+
+/// Which kind of object a metadata search returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchScope {
+    #[default]
+    DataObjects,
+    Collections,
+    Both,
+}
+
+#[derive(Debug)]
+pub enum SearchResult {
+    DataObject(DataObject),
+    Collection(Collection),
+}
+
+/// Finds the objects carrying AVUs that meet every condition, like
+/// `imeta qu`. Each condition must be met by a different AVU of the same
+/// object.
+pub struct MetadataSearch<'conn, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    conn: &'conn mut Connection<T, C>,
+    scope: SearchScope,
+    prefix: Option<PathBuf>,
+    conditions: Vec<(String, IcatPredicate)>,
+    max_results: u32,
+}
+
+impl<'conn, T, C> MetadataSearch<'conn, T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    pub fn new(conn: &'conn mut Connection<T, C>) -> Self {
+        Self {
+            conn,
+            scope: SearchScope::default(),
+            prefix: None,
+            conditions: Vec::new(),
+            max_results: 0,
+        }
+    }
+
+    pub fn scope(mut self, scope: SearchScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Only return objects in the tree under `path`.
+    pub fn under(mut self, path: &Path) -> Self {
+        self.prefix = Some(path.to_path_buf());
+        self
+    }
+
+    /// Requires an AVU with the attribute `attribute` whose value meets
+    /// `predicate`.
+    pub fn avu(mut self, attribute: &str, predicate: IcatPredicate) -> Self {
+        self.conditions.push((attribute.to_owned(), predicate));
+        self
+    }
+
+    /// Stops after this many results of each kind. Zero means no limit.
+    pub fn max_results(mut self, max_results: u32) -> Self {
+        self.max_results = max_results;
+        self
+    }
+
+    // The catalog joins a separate copy of the AVU table for each repeated
+    // attribute column, pairing the nth name with the nth value condition
+    fn make_query(&self, target: AVUTarget, path_column: IcatColumn) -> QueryBuilder {
+        let columns = AVUColumns::for_target(target);
+
+        // The server takes zero rows as a request to close the query
+        let mut builder = match self.max_results {
+            0 => QueryBuilder::new(),
+            max_results => QueryBuilder::new().max_rows(max_results),
+        };
+
+        for (attribute, predicate) in &self.conditions {
+            builder = builder
+                .condition(columns.attribute, IcatPredicate::Equals(attribute.clone()))
+                .condition(columns.value, predicate.clone());
+        }
+
+        // The prefix cannot tell /a/b from /a/bc, which the caller filters
+        match &self.prefix {
+            Some(prefix) => builder.condition(
+                path_column,
+                IcatPredicate::Like(format!("{}%", prefix.to_str().unwrap())),
+            ),
+            None => builder,
+        }
+    }
+
+    fn make_data_object_query(&self) -> GenQueryInp {
+        self.make_query(AVUTarget::DataObject, IcatColumn::CollectionName)
+            .select(IcatColumn::CollectionName)
+            .select(IcatColumn::DataObjectId)
+            .select(IcatColumn::DataObjectBaseName)
+            .select(IcatColumn::DataObjectSize)
+            .select(IcatColumn::DataObjectTypeName)
+            .select(IcatColumn::DataObjectReplNum)
+            .select(IcatColumn::DataObjectOwnerName)
+            .select(IcatColumn::DataObjectChecksum)
+            .select(IcatColumn::DataObjectReplicastatus)
+            .select(IcatColumn::DataObjectResourceName)
+            .select(IcatColumn::DataObjectPhysicalPath)
+            .select(IcatColumn::DataObjectResourceHierarchy)
+            .select(IcatColumn::DataObjectCreateTime)
+            .select(IcatColumn::DataObjectModifyTime)
+            .select(IcatColumn::DataObjectCollectionId)
+            .build()
+    }
+
+    fn make_collection_query(&self) -> GenQueryInp {
+        self.make_query(AVUTarget::Collection, IcatColumn::CollectionName)
+            .select(IcatColumn::CollectionId)
+            .select(IcatColumn::CollectionName)
+            .select(IcatColumn::CollectionOwnerName)
+            .select(IcatColumn::CollectionModifyTime)
+            .select(IcatColumn::CollectionCreateTime)
+            .select(IcatColumn::CollectionType)
+            .select(IcatColumn::CollectionInfo1)
+            .select(IcatColumn::CollectionInfo2)
+            .build()
+    }
+
+    pub fn execute(self) -> impl Stream<Item = Result<SearchResult, IrodsError>> + 'conn {
+        let data_objects = matches!(self.scope, SearchScope::DataObjects | SearchScope::Both)
+            .then(|| self.make_data_object_query());
+        let collections = matches!(self.scope, SearchScope::Collections | SearchScope::Both)
+            .then(|| self.make_collection_query());
+
+        let no_conditions = self.conditions.is_empty();
+        let Self { conn, prefix, .. } = self;
+
+        let in_tree = move |path: &Path| prefix.as_ref().map_or(true, |p| path.starts_with(p));
+
+        try_stream! {
+            if no_conditions {
+                Err::<(), _>(IrodsError::Other(
+                    "A metadata search needs at least one condition".into(),
+                ))?;
+            }
+
+            if let Some(mut inp) = data_objects {
+                for await row in conn.query(&mut inp).await {
+                    let mut row = row?;
+
+                    let coll = PathBuf::from(row.take(IcatColumn::CollectionName).ok_or_else(
+                        || IrodsError::Other("Missing collection name".to_owned()),
+                    )?);
+
+                    if in_tree(&coll) {
+                        yield SearchResult::DataObject(DataObject::try_from_row_and_collection(
+                            &mut row, &coll,
+                        )?);
+                    }
+                }
+            }
+
+            if let Some(mut inp) = collections {
+                for await row in conn.query(&mut inp).await {
+                    let mut row = row?;
+
+                    // The name is the full path, which replaces the parent
+                    let collection =
+                        Collection::try_from_row_and_parent_collection(&mut row, Path::new("/"))?;
+
+                    if in_tree(&collection.path) {
+                        yield SearchResult::Collection(collection);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Searches the catalog for objects by their AVUs.
+    pub fn search_metadata(&mut self) -> MetadataSearch<T, C> {
+        MetadataSearch::new(self)
+    }
+}
@@ -5,7 +5,7 @@ use crate::error::errors::IrodsError;
 
 use crate::{
     bosd::ProtocolEncoding,
-    common::APN,
+    common::{error_code, APN},
     connection::Connection,
     msg::{data_obj_inp::DataObjInp, header::MsgType, stat::RodsObjStat},
 };
@@ -37,4 +37,14 @@ where
 
         Ok(stat)
     }
+
+    /// Like `stat`, but a path that does not exist is `None` rather than an
+    /// error.
+    pub async fn try_stat(&mut self, path: &Path) -> Result<Option<RodsObjStat>, IrodsError> {
+        match self.stat(path).await {
+            Ok(stat) => Ok(Some(stat)),
+            Err(IrodsError::Server(error_code::USER_FILE_DOES_NOT_EXIST)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
 }
@@ -0,0 +1,459 @@
+use std::{
+    future::Future,
+    ops::{Deref, DerefMut},
+    path::PathBuf,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use deadpool::managed::Object;
+use futures::StreamExt;
+use tokio::task::JoinHandle;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    connection::{
+        authenticate::Authenticate,
+        connect::Connect,
+        pool::{ConnectionPool, IrodsManager},
+        Connection,
+    },
+    error::errors::IrodsError,
+};
+
+/// Outcome of every file visited by an upload.
+#[derive(Debug, Default)]
+pub struct TransferSummary {
+    pub succeeded: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, IrodsError)>,
+}
+
+/// Shared flag a running transfer polls between units of work.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationFlag(Arc<AtomicBool>);
+
+impl CancellationFlag {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn check(&self) -> Result<(), IrodsError> {
+        match self.is_cancelled() {
+            true => Err(IrodsError::Cancelled),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Handle to a transfer running on the tokio runtime. Awaiting it yields
+/// the transfer's result.
+///
+/// Cancellation is cooperative: the transfer stops before its next object
+/// or slice, so no pooled connection is left in the middle of a request.
+pub struct TransferHandle<R> {
+    inner: JoinHandle<Result<R, IrodsError>>,
+    cancelled: CancellationFlag,
+}
+
+impl<R> TransferHandle<R>
+where
+    R: Send + 'static,
+{
+    pub(crate) fn spawn<F>(fut: F, cancelled: CancellationFlag) -> Self
+    where
+        F: Future<Output = Result<R, IrodsError>> + Send + 'static,
+    {
+        Self {
+            inner: tokio::spawn(fut),
+            cancelled,
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.cancel();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.inner.is_finished()
+    }
+}
+
+impl<R> Future for TransferHandle<R> {
+    type Output = Result<R, IrodsError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.inner).poll(cx) {
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(IrodsError::Other(format!(
+                "Transfer task failed: {}",
+                e
+            )))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// How a failed slice of a parallel transfer is retried. Each retry runs on
+/// a fresh connection from the pool.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            initial_backoff,
+            max_backoff,
+        }
+    }
+
+    pub fn no_retries() -> Self {
+        Self::new(0, Duration::ZERO, Duration::ZERO)
+    }
+
+    /// Exponential backoff before retry number `attempt`, starting from 0.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+
+    /// Runs `transfer` until it succeeds or the retries run out, in which
+    /// case the last error is returned. Cancellation is checked before
+    /// every attempt.
+    pub(crate) async fn run<F, Fut, R>(
+        &self,
+        cancelled: &CancellationFlag,
+        mut transfer: F,
+    ) -> Result<R, IrodsError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<R, IrodsError>>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            cancelled.check()?;
+
+            let err = match transfer().await {
+                Ok(out) => return Ok(out),
+                Err(err) => err,
+            };
+
+            if attempt >= self.max_retries {
+                return Err(err);
+            }
+
+            tokio::time::sleep(self.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(100), Duration::from_secs(5))
+    }
+}
+
+/// Splits `size` bytes into at most `num_slices` contiguous `(offset, len)`
+/// ranges. Only the last slice may be shorter than the others.
+pub(crate) fn split_into_slices(size: usize, num_slices: u32) -> Vec<(usize, usize)> {
+    let len_per_slice = size.div_ceil(num_slices.max(1) as usize).max(1);
+
+    (0..size)
+        .step_by(len_per_slice)
+        .map(|offset| (offset, len_per_slice.min(size - offset)))
+        .collect()
+}
+
+/// Runs `transfer` on every item, at most `max_concurrent` at a time, and
+/// adds each failure to `failures`. Unless `continue_on_error` is set, the
+/// first failure stops the run and every failure so far is returned.
+pub(crate) async fn run_bounded<I, F, Fut>(
+    items: I,
+    max_concurrent: usize,
+    continue_on_error: bool,
+    cancelled: &CancellationFlag,
+    failures: &mut Vec<(PathBuf, IrodsError)>,
+    transfer: F,
+) -> Result<(), IrodsError>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future<Output = (PathBuf, Result<(), IrodsError>)>,
+{
+    let mut transfers = futures::stream::iter(items)
+        .map(transfer)
+        .buffer_unordered(max_concurrent.max(1));
+
+    while let Some((path, result)) = transfers.next().await {
+        if let Err(err) = result {
+            failures.push((path, err));
+
+            if !continue_on_error || cancelled.is_cancelled() {
+                return Err(IrodsError::TransferFailed(std::mem::take(failures)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A connection used for one slice of a parallel transfer. Either borrowed
+/// from the pool or opened directly to the resource server holding the
+/// replica.
+pub(crate) enum TransferConnection<T, C, A>
+where
+    T: ProtocolEncoding + Send + Sync,
+    C: Connect<T> + Send + Sync + 'static,
+    C::Transport: Send + Sync + 'static,
+    A: Authenticate<T, C::Transport> + Send + Sync + 'static,
+{
+    Pooled(Object<IrodsManager<T, C, A>>),
+    Direct(Connection<T, C::Transport>),
+}
+
+impl<T, C, A> TransferConnection<T, C, A>
+where
+    T: ProtocolEncoding + Send + Sync,
+    C: Connect<T> + Send + Sync + 'static,
+    C::Transport: Send + Sync + 'static,
+    A: Authenticate<T, C::Transport> + Send + Sync + 'static,
+{
+    pub(crate) async fn get(
+        pool: &ConnectionPool<T, C, A>,
+        host: Option<&str>,
+    ) -> Result<Self, IrodsError> {
+        match host {
+            Some(host) => Ok(Self::Direct(pool.manager().connect_to_host(host).await?)),
+            None => Ok(Self::Pooled(pool.get().await.map_err(|_| {
+                IrodsError::Other("Failed to get connection".to_string())
+            })?)),
+        }
+    }
+
+    /// Drops a connection that may have failed mid-message without
+    /// returning it to the pool.
+    pub(crate) fn discard(self) {
+        if let Self::Pooled(conn) = self {
+            let _ = Object::take(conn);
+        }
+    }
+}
+
+impl<T, C, A> Deref for TransferConnection<T, C, A>
+where
+    T: ProtocolEncoding + Send + Sync,
+    C: Connect<T> + Send + Sync + 'static,
+    C::Transport: Send + Sync + 'static,
+    A: Authenticate<T, C::Transport> + Send + Sync + 'static,
+{
+    type Target = Connection<T, C::Transport>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Pooled(conn) => conn,
+            Self::Direct(conn) => conn,
+        }
+    }
+}
+
+impl<T, C, A> DerefMut for TransferConnection<T, C, A>
+where
+    T: ProtocolEncoding + Send + Sync,
+    C: Connect<T> + Send + Sync + 'static,
+    C::Transport: Send + Sync + 'static,
+    A: Authenticate<T, C::Transport> + Send + Sync + 'static,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Pooled(conn) => conn,
+            Self::Direct(conn) => conn,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[test]
+    fn test_split_into_slices() {
+        assert_eq!(split_into_slices(10, 3), vec![(0, 4), (4, 4), (8, 2)]);
+        assert_eq!(split_into_slices(9, 3), vec![(0, 3), (3, 3), (6, 3)]);
+        assert_eq!(split_into_slices(2, 4), vec![(0, 1), (1, 1)]);
+        assert_eq!(split_into_slices(0, 4), vec![]);
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(100), Duration::from_millis(350));
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(350));
+        assert_eq!(policy.backoff(40), Duration::from_millis(350));
+    }
+
+    // Paths ending in "bad" fail after a short wait
+    async fn fake_transfer(
+        path: &'static str,
+        running: &AtomicUsize,
+        max_running: &AtomicUsize,
+    ) -> (PathBuf, Result<(), IrodsError>) {
+        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+        max_running.fetch_max(now, Ordering::SeqCst);
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        running.fetch_sub(1, Ordering::SeqCst);
+
+        let result = match path.ends_with("bad") {
+            true => Err(IrodsError::Other("failed".into())),
+            false => Ok(()),
+        };
+
+        (PathBuf::from(path), result)
+    }
+
+    #[tokio::test]
+    async fn test_run_bounded_limits_concurrency() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+
+        let mut failures = Vec::new();
+
+        run_bounded(
+            ["a", "b", "c", "d", "e", "f"],
+            2,
+            false,
+            &CancellationFlag::default(),
+            &mut failures,
+            |path| fake_transfer(path, &running, &max_running),
+        )
+        .await
+        .unwrap();
+
+        assert!(failures.is_empty());
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_bounded_collects_failures() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+
+        let mut failures = Vec::new();
+
+        run_bounded(
+            ["a", "b_bad", "c", "d_bad", "e"],
+            2,
+            true,
+            &CancellationFlag::default(),
+            &mut failures,
+            |path| fake_transfer(path, &running, &max_running),
+        )
+        .await
+        .unwrap();
+
+        let mut failed: Vec<_> = failures.iter().map(|(path, _)| path.clone()).collect();
+        failed.sort();
+        assert_eq!(failed, vec![PathBuf::from("b_bad"), PathBuf::from("d_bad")]);
+    }
+
+    #[tokio::test]
+    async fn test_run_bounded_stops_at_first_failure() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+
+        let mut failures = Vec::new();
+
+        let err = run_bounded(
+            ["a_bad", "b", "c", "d"],
+            1,
+            false,
+            &CancellationFlag::default(),
+            &mut failures,
+            |path| fake_transfer(path, &running, &max_running),
+        )
+        .await
+        .unwrap_err();
+
+        match err {
+            IrodsError::TransferFailed(failed) => {
+                assert_eq!(failed.len(), 1);
+                assert_eq!(failed[0].0, PathBuf::from("a_bad"));
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    // Fails the first `failures` attempts
+    async fn flaky_slice(attempts: &AtomicUsize, failures: usize) -> Result<usize, IrodsError> {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+
+        match attempt < failures {
+            true => Err(IrodsError::Other(format!("attempt {} failed", attempt))),
+            false => Ok(attempt),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_recovers_failed_slice() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(1));
+        let attempts = AtomicUsize::new(0);
+
+        let out = policy
+            .run(&CancellationFlag::default(), || flaky_slice(&attempts, 2))
+            .await
+            .unwrap();
+
+        assert_eq!(out, 2);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_with_last_error() {
+        let policy = RetryPolicy::new(2, Duration::from_millis(1), Duration::from_millis(1));
+        let attempts = AtomicUsize::new(0);
+
+        let err = policy
+            .run(&CancellationFlag::default(), || flaky_slice(&attempts, 10))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, IrodsError::Other(msg) if msg == "attempt 2 failed"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_when_cancelled() {
+        let cancelled = CancellationFlag::default();
+        cancelled.cancel();
+
+        let attempts = AtomicUsize::new(0);
+
+        let err = RetryPolicy::default()
+            .run(&cancelled, || flaky_slice(&attempts, 0))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, IrodsError::Cancelled));
+        assert_eq!(attempts.load(Ordering::SeqCst), 0);
+    }
+}
@@ -1,39 +1,52 @@
 use std::{
+    collections::VecDeque,
     fs::{Metadata, OpenOptions},
     os::unix::fs::FileExt,
-    path::Path,
+    path::{Path, PathBuf},
 };
 
-use futures::{future::BoxFuture, pin_mut, stream::FuturesUnordered, FutureExt, StreamExt};
-use md5::digest::consts::P562949953421312;
+use deadpool::managed::Object;
+use futures::{stream::FuturesUnordered, StreamExt};
 
 use crate::{
     bosd::ProtocolEncoding,
-    common::ObjectType,
     connection::{authenticate::Authenticate, connect::Connect, pool::ConnectionPool, Connection},
     error::errors::IrodsError,
 };
 
-use super::OpenFlag;
+use super::{
+    delete::DeleteRequest,
+    transfer::{
+        split_into_slices, CancellationFlag, RetryPolicy, TransferConnection, TransferHandle,
+        TransferSummary,
+    },
+    OpenFlag,
+};
 
-pub struct ParallelTransferContext<'pool, 'path, T, C, A>
+pub struct ParallelTransferContext<T, C, A>
 where
     T: ProtocolEncoding + Send + Sync,
     C: Connect<T> + Send + Sync + 'static,
     C::Transport: Send + Sync + 'static,
     A: Authenticate<T, C::Transport> + Send + Sync + 'static,
 {
-    pool: &'pool mut ConnectionPool<T, C, A>,
+    pool: ConnectionPool<T, C, A>,
     num_tasks: u32,
-    remote_path: &'path Path,
-    local_path: &'path Path,
+    remote_path: PathBuf,
+    local_path: PathBuf,
     resource: Option<String>,
     force_overwrite: bool,
     recursive: bool,
     max_size_before_parallel: usize,
+    max_concurrent_uploads: usize,
+    cancelled: CancellationFlag,
+    retry_policy: RetryPolicy,
+    keep_partial: bool,
+    redirect: bool,
+    portal: bool,
 }
 
-impl<'pool, 'path, T, C, A> ParallelTransferContext<'pool, 'path, T, C, A>
+impl<T, C, A> ParallelTransferContext<T, C, A>
 where
     T: ProtocolEncoding + Send + Sync,
     C: Connect<T> + Send + Sync + 'static,
@@ -41,10 +54,10 @@ where
     A: Authenticate<T, C::Transport> + Send + Sync + 'static,
 {
     pub fn new(
-        pool: &'pool mut ConnectionPool<T, C, A>,
+        pool: ConnectionPool<T, C, A>,
         num_tasks: u32,
-        remote_path: &'path Path,
-        local_path: &'path Path,
+        remote_path: PathBuf,
+        local_path: PathBuf,
     ) -> Self {
         Self {
             pool,
@@ -55,6 +68,12 @@ where
             force_overwrite: false,
             recursive: false,
             max_size_before_parallel: 32 * (1024_usize.pow(2)), // Default from PRC
+            max_concurrent_uploads: 8,
+            cancelled: CancellationFlag::default(),
+            retry_policy: RetryPolicy::default(),
+            keep_partial: false,
+            redirect: false,
+            portal: false,
         }
     }
 
@@ -83,40 +102,95 @@ where
         self
     }
 
-    pub async fn upload(mut self) -> Result<(), IrodsError> {
-        println!("{:?}", self.local_path);
-        let meta = self.local_path.metadata().map_err(|e| {
-            println!("{:?}", e);
-            IrodsError::Other("Failed to stat local path".into())
-        })?;
+    /// Upper bound on the number of files uploaded at once during a
+    /// recursive upload.
+    pub fn max_concurrent_uploads(mut self, max: usize) -> Self {
+        self.max_concurrent_uploads = max.max(1);
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Leave a partially written data object in place when a parallel
+    /// upload fails instead of removing it.
+    pub fn keep_partial(mut self) -> Self {
+        self.keep_partial = true;
+        self
+    }
+
+    /// Send the slices of a parallel upload straight to the resource
+    /// server the data object will land on rather than through the
+    /// server the pool is connected to.
+    pub fn redirect_to_resource_server(mut self) -> Self {
+        self.redirect = true;
+        self
+    }
+
+    /// Send large files through the server's portal, the way the iRODS
+    /// clients do, instead of writing each slice through its own open of
+    /// the data object.
+    pub fn use_portal(mut self) -> Self {
+        self.portal = true;
+        self
+    }
+
+    /// Runs the upload as its own task. The context owns everything it
+    /// needs, so several uploads can share one pool.
+    pub fn spawn(self) -> TransferHandle<TransferSummary>
+    where
+        T: 'static,
+    {
+        let cancelled = self.cancelled.clone();
+
+        TransferHandle::spawn(self.upload(), cancelled)
+    }
 
-        tokio::task::yield_now().await;
+    pub async fn upload(self) -> Result<TransferSummary, IrodsError> {
+        let meta = self
+            .local_path
+            .metadata()
+            .map_err(|_| IrodsError::Other("Failed to stat local path".into()))?;
 
         if meta.is_file() {
-            self.upload_file(self.local_path, self.remote_path, meta)
+            if let Some(parent) = self.remote_path.parent() {
+                let mut conn = self
+                    .pool
+                    .get()
+                    .await
+                    .map_err(|_| IrodsError::Other("Failed to get connection".into()))?;
+
+                conn.ensure_collection(parent).await?;
+            }
+
+            self.upload_file(&self.local_path, &self.remote_path, meta)
                 .await?;
+
+            Ok(TransferSummary {
+                succeeded: vec![self.local_path.clone()],
+                failed: Vec::new(),
+            })
         } else if meta.is_dir() && self.recursive {
-            println!("Uploading dir");
-            self.upload_dir(self.local_path, self.remote_path, meta)
-                .await?;
+            self.upload_dir(&self.local_path, &self.remote_path).await
         } else if meta.is_dir() {
-            return Err(IrodsError::Other(
+            Err(IrodsError::Other(
                 "Path is a directory and recursive flag not set".into(),
-            ));
+            ))
         } else {
-            return Err(IrodsError::Other("Path is not a file or directory".into()));
+            Err(IrodsError::Other("Path is not a file or directory".into()))
         }
-
-        Ok(())
     }
 
     pub async fn upload_file(
-        &mut self,
+        &self,
         local_path: &Path,
         remote_path: &Path,
         meta: Metadata,
     ) -> Result<(), IrodsError> {
-        println!("Uploading file");
+        self.cancelled.check()?;
+
         if meta.len() > self.max_size_before_parallel as u64 {
             return self
                 .upload_file_parallel(local_path, remote_path, meta)
@@ -129,116 +203,294 @@ where
             .await
             .map_err(|_| IrodsError::Other("Failed to get connection".into()))?;
 
-        let stat = conn.stat(self.remote_path).await?;
-        match stat.object_type {
-            ObjectType::UnknownObj => {
-                conn.do_parallel_upload_task(
-                    self.remote_path,
-                    self.local_path,
-                    self.resource.clone(),
-                    0,
-                    meta.len() as usize,
-                )
-                .await?
-            }
-            ObjectType::Coll | ObjectType::DataObj if self.force_overwrite => {
-                conn.do_parallel_upload_task(
-                    self.remote_path,
-                    self.local_path,
-                    self.resource.clone(),
-                    0,
-                    meta.len() as usize,
-                )
-                .await?
+        let len = meta.len() as usize;
+        let file = OpenOptions::new().read(true).open(local_path)?;
+
+        let mut buf = std::mem::take(&mut conn.resources.bytes_buf);
+
+        // The buffer comes back even if the read fails
+        let (mut buf, read) = tokio::task::spawn_blocking(move || {
+            if buf.len() < len {
+                buf.resize(len, 0);
             }
-            _ => {
+            let read = file.read_exact_at(&mut buf[..len], 0);
+
+            (buf, read)
+        })
+        .await
+        .map_err(|_| IrodsError::Other("Failed to transfer file".to_string()))?;
+
+        if let Err(err) = read {
+            conn.resources.bytes_buf = buf;
+            return Err(err.into());
+        }
+
+        // The server refuses to overwrite an existing object unless forced,
+        // so there is no need to stat first
+        let request = conn.put_request(remote_path).force(self.force_overwrite);
+        let request = match self.resource.as_deref() {
+            Some(resc) => request.set_resc(resc),
+            None => request,
+        };
+        let result = request.execute(&buf[..len]).await;
+
+        std::mem::swap(&mut conn.resources.bytes_buf, &mut buf);
+
+        result
+    }
+
+    pub async fn upload_file_parallel(
+        &self,
+        local_path: &Path,
+        remote_path: &Path,
+        meta: Metadata,
+    ) -> Result<(), IrodsError> {
+        if self.portal {
+            return self.upload_file_via_portal(local_path, remote_path).await;
+        }
+
+        let host = match self.redirect {
+            true => self.host_for_put(remote_path).await?,
+            false => None,
+        };
+        let host = host.as_deref();
+
+        // Every slice writes to the replica opened here, through its replica
+        // token, so the upload ends up as a single good replica
+        let mut primary = TransferConnection::get(&self.pool, host).await?;
+
+        if !self.force_overwrite {
+            let stat = primary.try_stat(remote_path).await?;
+
+            if stat.is_some() {
                 return Err(IrodsError::Other(
                     "Remote path already exists and overwrite flag not set".into(),
                 ));
             }
         }
 
-        Ok(())
+        let request = primary
+            .open_request(remote_path)
+            .set_flag(OpenFlag::WriteOnly)
+            .set_flag(OpenFlag::Create)
+            .set_flag(OpenFlag::Truncate);
+        let request = match self.resource.as_deref() {
+            Some(resc) => request.set_resc(resc),
+            None => request,
+        };
+        let handle = request.execute().await?;
+
+        let info = match primary.get_file_descriptor_info(handle).await {
+            Ok(info) => info,
+            Err(err) => {
+                let _ = primary.replica_close_request(handle).execute().await;
+                return Err(err);
+            }
+        };
+        let replica = (
+            info.replica_token.as_str(),
+            info.data_object_info.resource_hierarchy.as_str(),
+        );
+
+        let slices = split_into_slices(meta.len() as usize, self.num_tasks);
+        let num_slices = slices.len();
+
+        let mut futs: FuturesUnordered<_> = slices
+            .into_iter()
+            .enumerate()
+            .map(|(slice, (offset, len))| async move {
+                let result = self
+                    .upload_slice(local_path, remote_path, host, replica, offset, len)
+                    .await;
+                (slice, result)
+            })
+            .collect();
+
+        let mut failures = Vec::new();
+
+        while let Some((slice, result)) = futs.next().await {
+            if let Err(err) = result {
+                failures.push((slice, err));
+            }
+        }
+
+        // Closed last, so this close is the one that finalizes the replica
+        let closed = primary.replica_close_request(handle).execute().await;
+
+        if closed.is_err() {
+            primary.discard();
+        }
+
+        if failures.is_empty() {
+            return closed;
+        }
+
+        if !self.keep_partial {
+            if let Ok(mut conn) = self.pool.get().await {
+                let _ = DeleteRequest::new(&mut *conn, remote_path)
+                    .force(true)
+                    .execute()
+                    .await;
+            }
+        }
+
+        Err(IrodsError::SlicesFailed {
+            path: local_path.to_path_buf(),
+            num_slices,
+            failures,
+        })
     }
 
-    pub async fn upload_file_parallel(
-        &mut self,
+    async fn upload_file_via_portal(
+        &self,
         local_path: &Path,
         remote_path: &Path,
-        meta: Metadata,
     ) -> Result<(), IrodsError> {
-        let size = meta.len() as usize;
-        let len_per_task = ((size as f64 / self.num_tasks as f64).floor() as usize)
-            + ((((size as u32) % self.num_tasks != 0) as usize) as usize);
-
-        let futs = FuturesUnordered::new();
-
-        for task in 0..self.num_tasks {
-            let resource = self.resource.clone();
-            let remote_path = remote_path.to_path_buf();
-            let local_path = local_path.to_path_buf();
-
-            let mut conn = self
-                .pool
-                .get()
-                .await
-                .map_err(|_| IrodsError::Other("Failed to get connection".to_string()))?;
-
-            futs.push(async move {
-                conn.do_parallel_upload_task(
-                    &remote_path,
-                    &local_path,
-                    resource,
-                    task as usize,
-                    len_per_task,
-                )
-                .await?;
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|_| IrodsError::Other("Failed to get connection".into()))?;
+
+        let request = conn
+            .put_request(remote_path)
+            .force(self.force_overwrite)
+            .num_threads(self.num_tasks);
+        let request = match self.resource.as_deref() {
+            Some(resc) => request.set_resc(resc),
+            None => request,
+        };
+        let result = request.execute_from_file(local_path).await;
 
-                Ok::<_, IrodsError>(())
-            });
+        if result.is_err() {
+            let _ = Object::take(conn);
         }
 
-        futs.collect::<Vec<_>>().await;
+        result
+    }
 
-        Ok(())
+    async fn host_for_put(&self, remote_path: &Path) -> Result<Option<String>, IrodsError> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|_| IrodsError::Other("Failed to get connection".into()))?;
+
+        conn.get_host_for_put(remote_path, self.resource.as_deref())
+            .await
     }
 
-    pub fn upload_dir<'this, 'd>(
-        &'this mut self,
-        local_path: &'d Path,
-        remote_path: &'d Path,
-        meta: Metadata,
-    ) -> BoxFuture<'this, Result<(), IrodsError>>
-    where
-        'd: 'this,
-    {
-        println!("Uploading dir: {:?} to {:?}", local_path, remote_path);
-        async move {
-            let mut conn = self
-                .pool
-                .get()
-                .await
-                .map_err(|_| IrodsError::Other("Failed to get connection".into()))?;
-
-            // Why is this not an actual Stream?
-            // The mysteries of Rust async programming
-            let mut entries = tokio::fs::read_dir(local_path).await?;
-
-            while let Ok(Some(entry)) = entries.next_entry().await {
+    async fn upload_slice(
+        &self,
+        local_path: &Path,
+        remote_path: &Path,
+        host: Option<&str>,
+        replica: (&str, &str),
+        offset: usize,
+        len: usize,
+    ) -> Result<(), IrodsError> {
+        self.retry_policy
+            .run(&self.cancelled, || async {
+                let mut conn = TransferConnection::get(&self.pool, host).await?;
+
+                let result = conn
+                    .do_parallel_upload_task(remote_path, local_path, replica, offset, len)
+                    .await;
+
+                // The connection may have failed mid-message, so it
+                // must not go back to the pool
+                if result.is_err() {
+                    conn.discard();
+                }
+
+                result
+            })
+            .await
+    }
+
+    pub async fn upload_dir(
+        &self,
+        local_path: &Path,
+        remote_path: &Path,
+    ) -> Result<TransferSummary, IrodsError> {
+        let (small, large) = self.create_collection_tree(local_path, remote_path).await?;
+
+        let mut summary = TransferSummary::default();
+
+        let mut uploads = futures::stream::iter(small)
+            .map(|(local_path, remote_path, meta)| async move {
+                let result = self.upload_file(&local_path, &remote_path, meta).await;
+                (local_path, result)
+            })
+            .buffer_unordered(self.max_concurrent_uploads);
+
+        while let Some((path, result)) = uploads.next().await {
+            match result {
+                Ok(()) => summary.succeeded.push(path),
+                Err(err) => summary.failed.push((path, err)),
+            }
+        }
+
+        // Large files are already split across `num_tasks` connections,
+        // so running them side by side could starve the pool.
+        for (local_path, remote_path, meta) in large {
+            match self.upload_file(&local_path, &remote_path, meta).await {
+                Ok(()) => summary.succeeded.push(local_path),
+                Err(err) => summary.failed.push((local_path, err)),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Mirrors the local directory tree under `remote_path`, creating each
+    /// collection before any of its children. Returns the files to upload,
+    /// split on `max_size_before_parallel`.
+    async fn create_collection_tree(
+        &self,
+        local_path: &Path,
+        remote_path: &Path,
+    ) -> Result<
+        (
+            Vec<(PathBuf, PathBuf, Metadata)>,
+            Vec<(PathBuf, PathBuf, Metadata)>,
+        ),
+        IrodsError,
+    > {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|_| IrodsError::Other("Failed to get connection".into()))?;
+
+        conn.ensure_collection(remote_path).await?;
+
+        let mut small = Vec::new();
+        let mut large = Vec::new();
+
+        let mut pending = VecDeque::from([(local_path.to_path_buf(), remote_path.to_path_buf())]);
+
+        while let Some((local_path, remote_path)) = pending.pop_front() {
+            let mut entries = tokio::fs::read_dir(&local_path).await?;
+
+            while let Some(entry) = entries.next_entry().await? {
                 let local_path = entry.path();
                 let remote_path = remote_path.join(entry.file_name());
 
                 let meta = entry.metadata().await?;
 
-                if meta.is_file() {
-                    self.upload_file(&local_path, &remote_path, meta).await?;
-                } else if meta.is_dir() && self.recursive {
-                    self.upload_dir(&local_path, &remote_path, meta).await?;
+                if meta.is_file() && meta.len() > self.max_size_before_parallel as u64 {
+                    large.push((local_path, remote_path, meta));
+                } else if meta.is_file() {
+                    small.push((local_path, remote_path, meta));
+                } else if meta.is_dir() {
+                    conn.ensure_collection(&remote_path).await?;
+                    pending.push_back((local_path, remote_path));
                 }
             }
-            Ok(())
         }
-        .boxed()
+
+        Ok((small, large))
     }
 }
 
@@ -251,28 +503,23 @@ where
         &mut self,
         remote_path: &Path,
         local_path: &Path,
-        resource: Option<String>,
-        task: usize,
+        replica: (&str, &str),
+        offset: usize,
         len: usize,
     ) -> Result<(), IrodsError> {
-        println!("Doing parallel upload task {} of length {}", task, len);
         let file = OpenOptions::new().read(true).open(local_path)?;
 
-        let handle = match resource {
-            Some(resc) => {
-                self.open_request(remote_path)
-                    .set_resc(resc.as_str())
-                    .set_flag(OpenFlag::WriteOnly)
-                    .execute()
-                    .await?
-            }
-            None => {
-                self.open_request(remote_path)
-                    .set_flag(OpenFlag::WriteOnly)
-                    .execute()
-                    .await?
-            }
-        };
+        let (token, resc_hier) = replica;
+        let handle = self
+            .open_request(remote_path)
+            .set_flag(OpenFlag::WriteOnly)
+            .set_replica_token(token, resc_hier)
+            .execute()
+            .await?;
+
+        if offset > 0 {
+            self.seek(handle, super::Whence::SeekSet, offset).await?;
+        }
 
         let mut buf = std::mem::take(&mut self.resources.bytes_buf);
 
@@ -281,7 +528,7 @@ where
             if buf.len() < len {
                 buf.resize(len, 0);
             }
-            file.read_exact_at(&mut buf[..len], (task * len) as u64)?;
+            file.read_exact_at(&mut buf[..len], offset as u64)?;
 
             Ok::<_, IrodsError>(buf)
         })
@@ -293,7 +540,10 @@ where
         // Send the contents of the bytes buf to the server
         self.write_data_obj_from_bytes_buf(handle, len).await?;
 
-        self.close(handle).await?;
+        self.replica_close_request(handle)
+            .secondary()
+            .execute()
+            .await?;
 
         Ok(())
     }
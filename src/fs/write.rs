@@ -1,7 +1,3 @@
-use std::io::Cursor;
-
-use tokio::io::AsyncReadExt;
-
 use crate::{
     bosd::ProtocolEncoding,
     common::APN,
@@ -26,21 +22,22 @@ where
         handle: DataObjectHandle,
         len: usize,
     ) -> Result<(), IrodsError> {
-        self.resources
-            .send_header_then_msg::<T, _>(
-                &Self::make_write_data_obj_inp(handle, self.resources.bytes_buf.len()),
+        let buf = std::mem::take(&mut self.resources.bytes_buf);
+
+        let result = self
+            .resources
+            .send_header_then_msg_and_bytes::<T, _>(
+                &Self::make_write_data_obj_inp(handle, len),
                 MsgType::RodsApiReq,
                 APN::DataObjWrite as i32,
+                &buf[..len],
             )
-            .await?;
+            .await;
 
-        tokio::io::copy(
-            &mut Cursor::new(&mut self.resources.bytes_buf).take(len as u64),
-            &mut self.resources.transport,
-        )
-        .await?;
+        self.resources.bytes_buf = buf;
+        result?;
 
-        self.resources.read_standard_header::<T>().await?;
+        self.resources.read_status::<T>().await?;
 
         Ok(())
     }
@@ -0,0 +1,67 @@
+use serde::de::DeserializeOwned;
+
+use crate::{
+    bosd::ProtocolEncoding,
+    common::APN,
+    connection::Connection,
+    error::errors::IrodsError,
+    msg::{genquery2::Genquery2Inp, header::MsgType, str_msg::StrMsg},
+};
+
+impl<T, C> Connection<T, C>
+where
+    T: ProtocolEncoding,
+    C: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Runs a GenQuery2 query, e.g.,
+    /// `select COLL_NAME, DATA_NAME where META_DATA_ATTR_NAME = 'run'`,
+    /// returning each row as the values of its columns. Needs iRODS 4.3.2
+    /// or later.
+    pub async fn genquery2(
+        &mut self,
+        query: &str,
+        zone: Option<&str>,
+    ) -> Result<Vec<Vec<String>>, IrodsError> {
+        self.genquery2_as(query, zone).await
+    }
+
+    /// Like `genquery2`, deserializing each row into `R`, which is read
+    /// from an array such as a tuple or a struct with fields in column order.
+    pub async fn genquery2_as<R>(
+        &mut self,
+        query: &str,
+        zone: Option<&str>,
+    ) -> Result<Vec<R>, IrodsError>
+    where
+        R: DeserializeOwned,
+    {
+        let out = self.send_genquery2(query, zone, false).await?;
+
+        Ok(serde_json::from_str(&out)?)
+    }
+
+    /// The SQL the query translates to, without running it.
+    pub async fn genquery2_sql(
+        &mut self,
+        query: &str,
+        zone: Option<&str>,
+    ) -> Result<String, IrodsError> {
+        self.send_genquery2(query, zone, true).await
+    }
+
+    async fn send_genquery2(
+        &mut self,
+        query: &str,
+        zone: Option<&str>,
+        sql_only: bool,
+    ) -> Result<String, IrodsError> {
+        let inp = Genquery2Inp::new(query.to_owned(), zone.map(str::to_owned), sql_only);
+
+        self.send_header_then_msg(&inp, MsgType::RodsApiReq, APN::GenQuery2 as i32)
+            .await?;
+
+        let (_, out) = self.get_header_and_msg::<StrMsg>().await?;
+
+        Ok(out.my_str)
+    }
+}
@@ -1,12 +1,14 @@
+pub mod genquery2;
+
 use async_stream::try_stream;
 
 use crate::{
     bosd::ProtocolEncoding,
-    common::{icat_column::IcatColumn, APN},
+    common::{error_code, icat_column::IcatColumn, APN},
     connection::Connection,
     error::errors::IrodsError,
     msg::{
-        gen_query::{GenQueryInp, GenQueryOut},
+        gen_query::{GenQueryInp, GenQueryOut, AUTO_CLOSE, RETURN_TOTAL_ROW_COUNT},
         header::MsgType,
     },
 };
@@ -47,6 +49,19 @@ where
         Ok(out)
     }
 
+    /// Counts the rows matching `inp` without reading them.
+    pub async fn total_row_count(&mut self, inp: &GenQueryInp) -> Result<u32, IrodsError> {
+        let mut inp = inp.clone();
+        inp.flags |= RETURN_TOTAL_ROW_COUNT | AUTO_CLOSE;
+        inp.max_rows = 1;
+
+        match self.one_off_query(&inp).await {
+            Ok(out) => Ok(out.total_row_count),
+            Err(IrodsError::Server(error_code::CAT_NO_ROWS_FOUND)) => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+
     // TODO: Reimplement this in terms of futures::stream::(try_)unfold
     // and futures::stream::StreamExt::flatten. My assumption is that
     // since these don't use message passing, they should be more efficient.
@@ -61,7 +76,11 @@ where
             let mut more_pages = true;
             let mut rows_processed = 0;
             while more_pages {
-                let out = self.one_off_query(inp).await?;
+                // No matches is an empty result, not a failure
+                let out = match self.one_off_query(inp).await {
+                    Err(IrodsError::Server(error_code::CAT_NO_ROWS_FOUND)) => break,
+                    out => out?,
+                };
 
                 inp.continue_index = out.continue_index;
 
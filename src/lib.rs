@@ -18,6 +18,7 @@ use error::errors::IrodsError;
 pub use exec_rule_macro;
 pub use exec_rule_macro::rule;
 use gen_query::Row;
+use msg::spec_coll::SpecialCollectionClass;
 
 pub mod reexports {
     pub use derive_builder;
@@ -25,7 +26,7 @@ pub mod reexports {
     pub use tokio;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataObjectType {
     Generic,
     Tar,
@@ -35,6 +36,19 @@ pub enum DataObjectType {
     Msso,
 }
 
+impl Into<&'static str> for DataObjectType {
+    fn into(self) -> &'static str {
+        match self {
+            DataObjectType::Generic => "generic",
+            DataObjectType::Tar => "tar file",
+            DataObjectType::GzipTar => "gzipTar",
+            DataObjectType::Bzip2 => "bzip2Tar",
+            DataObjectType::Zip => "zipFile",
+            DataObjectType::Msso => "msso file",
+        }
+    }
+}
+
 impl TryFrom<&str> for DataObjectType {
     type Error = IrodsError;
 
@@ -51,12 +65,16 @@ impl TryFrom<&str> for DataObjectType {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct AccessControl {
-    path: PathBuf,
-    user_name: String,
-    user_zone: String,
-    user_type: UserType,
-    access_type: AccessLevel,
+    pub path: PathBuf,
+    pub user_name: String,
+    pub user_zone: String,
+    pub user_type: UserType,
+    pub access_type: AccessLevel,
+    /// The group the access was granted to, if the user only has it as one
+    /// of its members.
+    pub via_group: Option<String>,
 }
 
 impl AccessControl {
@@ -82,6 +100,7 @@ impl AccessControl {
                 .ok_or_else(|| IrodsError::Other("Missing access_type".to_owned()))?
                 .as_str()
                 .try_into()?,
+            via_group: None,
         })
     }
 
@@ -107,10 +126,19 @@ impl AccessControl {
                 .ok_or_else(|| IrodsError::Other("Missing access_type".to_owned()))?
                 .as_str()
                 .try_into()?,
+            via_group: None,
         })
     }
 }
 
+/// A member of a group, as listed by `list_users_in_group`.
+#[derive(Debug, Clone)]
+pub struct GroupMember {
+    pub name: String,
+    pub zone: String,
+    pub user_type: UserType,
+}
+
 #[derive(Debug)]
 pub struct DataObject {
     id: i64,
@@ -265,6 +293,33 @@ pub struct Collection {
     owner: String,
     create_time: DateTime<Utc>,
     modify_time: DateTime<Utc>,
+    special: Option<SpecialCollectionInfo>,
+}
+
+/// What the catalog records about a mounted, linked or structured-file
+/// collection.
+#[derive(Debug, Clone)]
+pub struct SpecialCollectionInfo {
+    pub class: SpecialCollectionClass,
+    /// The mounted directory, the linked collection or the structured file.
+    pub target: String,
+    /// For mounted directories and structured files, the resource, along
+    /// with the cache directory of the latter.
+    pub info: String,
+}
+
+impl SpecialCollectionInfo {
+    fn try_from_row(value: &mut Row) -> Option<Self> {
+        let class = SpecialCollectionClass::from_catalog_type(
+            value.at(IcatColumn::CollectionType)?.as_str(),
+        )?;
+
+        Some(Self {
+            class,
+            target: value.take(IcatColumn::CollectionInfo1).unwrap_or_default(),
+            info: value.take(IcatColumn::CollectionInfo2).unwrap_or_default(),
+        })
+    }
 }
 
 impl Collection {
@@ -306,8 +361,15 @@ impl Collection {
                     .at(IcatColumn::CollectionModifyTime)
                     .ok_or_else(|| IrodsError::Other("Missing modify_time".to_owned()))?,
             )?,
+
+            special: SpecialCollectionInfo::try_from_row(value),
         })
     }
+
+    /// Set if this is a mounted, linked or structured-file collection.
+    pub fn special_collection(&self) -> Option<&SpecialCollectionInfo> {
+        self.special.as_ref()
+    }
 }
 
 pub fn irods_instant(time: &str) -> Result<DateTime<Utc>, IrodsError> {
@@ -331,6 +393,7 @@ pub struct Entry {
     checksum_algo: ChecksumAlgo,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChecksumAlgo {
     SHA1,
     SHA256,
@@ -387,31 +450,81 @@ impl From<DataObject> for Entry {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AVU {
-    id: i64,
-    attribute: String,
-    value: String,
-    unit: String,
+    pub id: i64,
+    pub attribute: String,
+    pub value: String,
+    pub unit: String,
+}
+
+/// The catalog columns holding the AVUs of one kind of target.
+pub(crate) struct AVUColumns {
+    pub id: IcatColumn,
+    pub attribute: IcatColumn,
+    pub value: IcatColumn,
+    pub unit: IcatColumn,
+}
+
+impl AVUColumns {
+    pub(crate) fn for_target(target: AVUTarget) -> Self {
+        match target {
+            AVUTarget::DataObject => Self {
+                id: IcatColumn::MetadataAttributeId,
+                attribute: IcatColumn::MetadataAttributeName,
+                value: IcatColumn::MetadataAttributeValue,
+                unit: IcatColumn::MetadataAttributeUnits,
+            },
+            AVUTarget::Collection => Self {
+                id: IcatColumn::MetadataCollectionAttributeId,
+                attribute: IcatColumn::MetadataCollectionAttributeName,
+                value: IcatColumn::MetadataCollectionAttributeValue,
+                unit: IcatColumn::MetadataCollectionAttributeUnits,
+            },
+            AVUTarget::User => Self {
+                id: IcatColumn::MetaDataUserAttributeId,
+                attribute: IcatColumn::MetaDataUserAttributeName,
+                value: IcatColumn::MetaDataUserAttributeValue,
+                unit: IcatColumn::MetaDataUserAttributeUnits,
+            },
+            AVUTarget::Resource => Self {
+                id: IcatColumn::MetadataResourceAttributeId,
+                attribute: IcatColumn::MetadataResourceAttributeName,
+                value: IcatColumn::MetadataResourceAttributeValue,
+                unit: IcatColumn::MetadataResourceAttributeUnits,
+            },
+        }
+    }
 }
 
 impl AVU {
-    fn try_from_row(row: &mut Row) -> Result<Self, IrodsError> {
+    /// An AVU to add or look for. The id is only known for AVUs read from
+    /// the catalog.
+    pub fn new(attribute: &str, value: &str, unit: &str) -> Self {
+        Self {
+            id: 0,
+            attribute: attribute.to_owned(),
+            value: value.to_owned(),
+            unit: unit.to_owned(),
+        }
+    }
+
+    pub(crate) fn try_from_row(row: &mut Row, columns: &AVUColumns) -> Result<Self, IrodsError> {
         Ok(Self {
             id: row
-                .take(IcatColumn::MetadataAttributeId)
+                .take(columns.id)
                 .ok_or_else(|| IrodsError::Other("Missing id".to_owned()))?
                 .parse()?,
             attribute: row
-                .take(IcatColumn::MetadataAttributeName)
+                .take(columns.attribute)
                 .ok_or_else(|| IrodsError::Other("Missing attribute".to_owned()))?,
 
             value: row
-                .take(IcatColumn::MetadataAttributeValue)
+                .take(columns.value)
                 .ok_or_else(|| IrodsError::Other("Missing value".to_owned()))?,
 
             unit: row
-                .take(IcatColumn::MetadataAttributeUnits)
+                .take(columns.unit)
                 .ok_or_else(|| IrodsError::Other("Missing unit".to_owned()))?,
         })
     }
@@ -430,7 +543,7 @@ impl Into<&str> for AVUTarget {
         match self {
             AVUTarget::User => "-u",
             AVUTarget::Collection => "-C",
-            AVUTarget::DataObject => "-u",
+            AVUTarget::DataObject => "-d",
             AVUTarget::Resource => "-R",
         }
     }
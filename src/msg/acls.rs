@@ -22,6 +22,7 @@ pub struct ModifyAccessRequest {
     user_name: String,
     zone: String,
     path: PathBuf,
+    admin: bool,
 }
 
 impl ModifyAccessRequest {
@@ -38,6 +39,31 @@ impl ModifyAccessRequest {
             user_name,
             zone,
             path,
+            admin: false,
+        }
+    }
+
+    /// Lets a rodsadmin change access to objects it has no access to.
+    pub fn admin(mut self, admin: bool) -> Self {
+        self.admin = admin;
+        self
+    }
+
+    // The API spells the levels differently from the catalog, which
+    // records e.g. "read object"
+    fn access_level(&self) -> String {
+        let level = match self.access_level {
+            AccessLevel::Own => "own",
+            AccessLevel::Write => "write",
+            AccessLevel::Read => "read",
+            AccessLevel::NoAccess => "null",
+            AccessLevel::Inherit => "inherit",
+            AccessLevel::NoInherit => "noinherit",
+        };
+
+        match self.admin {
+            true => format!("admin:{level}"),
+            false => level.to_string(),
         }
     }
 }
@@ -51,7 +77,7 @@ impl XMLSerializable for ModifyAccessRequest {
         writer.write_event(Event::Start(BytesStart::new("modAccessControl_PI")))?;
 
         tag_fmt!(writer, "recursive", "{}", self.recursive as i32);
-        tag!(writer, "accessLevel", self.access_level.into());
+        tag!(writer, "accessLevel", &self.access_level());
         tag!(writer, "userName", self.user_name.as_str());
         tag!(writer, "zone", self.zone.as_str());
         tag!(writer, "path", self.path.to_str().unwrap());
@@ -61,3 +87,23 @@ impl XMLSerializable for ModifyAccessRequest {
         Ok(cursor.position() as usize)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn access_level_of(access_level: AccessLevel, admin: bool) -> String {
+        ModifyAccessRequest::new(false, access_level, "alice".into(), "".into(), "/z".into())
+            .admin(admin)
+            .access_level()
+    }
+
+    #[test]
+    fn access_levels_use_api_names() {
+        assert_eq!(access_level_of(AccessLevel::Own, false), "own");
+        assert_eq!(access_level_of(AccessLevel::Write, false), "write");
+        assert_eq!(access_level_of(AccessLevel::Read, false), "read");
+        assert_eq!(access_level_of(AccessLevel::NoAccess, false), "null");
+        assert_eq!(access_level_of(AccessLevel::Read, true), "admin:read");
+    }
+}
@@ -1,4 +1,3 @@
-use std::borrow::Cow;
 use std::io::Cursor;
 
 use crate::bosd::xml::irods_unescapes;
@@ -33,18 +32,8 @@ impl BinBytesBuf {
 
 impl Serialiazable for BinBytesBuf {}
 impl XMLSerializable for BinBytesBuf {
-    fn to_xml(&self, mut sink: &mut Vec<u8>) -> Result<usize, IrodsError> {
-        let mut cursor = Cursor::new(sink);
-        let mut writer = Writer::new(&mut cursor);
-
-        writer.write_event(Event::Start(BytesStart::new("BinBytesBuf_PI")))?;
-
-        tag_fmt!(writer, "buflen", "{}", self.buf.len());
-        tag!(writer, "buf", &self.buf);
-
-        writer.write_event(Event::End(BytesEnd::new("BinBytesBuf_PI")))?;
-
-        Ok(cursor.position() as usize)
+    fn to_xml(&self, sink: &mut Vec<u8>) -> Result<usize, IrodsError> {
+        buf_to_xml("BinBytesBuf_PI", &self.buf, sink)
     }
 }
 
@@ -54,44 +43,65 @@ impl XMLDeserializable for BinBytesBuf {
     where
         Self: Sized,
     {
-        #[derive(Debug)]
-        #[repr(u8)]
-        enum State {
-            Tag,
-            BufLen,
-            BufLenInner,
-            Buf,
-            BufInner,
-        }
-        let mut reader = quick_xml::Reader::from_reader(xml);
-        let mut state = State::Tag;
+        Ok(BinBytesBuf {
+            buf: buf_from_xml(b"BinBytesBuf_PI", xml)?,
+        })
+    }
+}
 
-        loop {
-            state = match (state, reader.read_event()?) {
-                (State::Tag, Event::Start(ref e)) if e.name().as_ref() == b"BinBytesBuf_PI" => {
-                    State::BufLen
-                }
-                (State::BufLen, Event::Start(ref e)) if e.name().as_ref() == b"buflen" => {
-                    State::BufLenInner
-                }
-                (State::BufLenInner, Event::Text(_)) => {
-                    // We don't actually care about the buf len
-                    State::Buf
-                }
-                (State::Buf, Event::Start(ref e)) if e.name().as_ref() == b"buf" => State::BufInner,
-                (State::BufInner, Event::Text(ref e)) => {
-                    return Ok(BinBytesBuf {
-                        buf: e.unescape_with(irods_unescapes)?.to_string(),
-                    });
-                }
-                (state, Event::Eof) => {
-                    return Err(IrodsError::Other(format!(
-                        "unexpected EOF in state: [{:?}]",
-                        state
-                    )))
-                }
-                (state, _) => state,
-            };
-        }
+// BinBytesBuf_PI and BytesBuf_PI share a layout and only differ in name
+pub(crate) fn buf_to_xml(
+    pi_name: &str,
+    buf: &str,
+    sink: &mut Vec<u8>,
+) -> Result<usize, IrodsError> {
+    let mut cursor = Cursor::new(sink);
+    let mut writer = Writer::new(&mut cursor);
+
+    writer.write_event(Event::Start(BytesStart::new(pi_name)))?;
+
+    tag_fmt!(writer, "buflen", "{}", buf.len());
+    tag!(writer, "buf", buf);
+
+    writer.write_event(Event::End(BytesEnd::new(pi_name)))?;
+
+    Ok(cursor.position() as usize)
+}
+
+pub(crate) fn buf_from_xml(pi_name: &[u8], xml: &[u8]) -> Result<String, IrodsError> {
+    #[derive(Debug)]
+    #[repr(u8)]
+    enum State {
+        Tag,
+        BufLen,
+        BufLenInner,
+        Buf,
+        BufInner,
+    }
+    let mut reader = quick_xml::Reader::from_reader(xml);
+    let mut state = State::Tag;
+
+    loop {
+        state = match (state, reader.read_event()?) {
+            (State::Tag, Event::Start(ref e)) if e.name().as_ref() == pi_name => State::BufLen,
+            (State::BufLen, Event::Start(ref e)) if e.name().as_ref() == b"buflen" => {
+                State::BufLenInner
+            }
+            (State::BufLenInner, Event::Text(_)) => {
+                // We don't actually care about the buf len
+                State::Buf
+            }
+            (State::Buf, Event::Start(ref e)) if e.name().as_ref() == b"buf" => State::BufInner,
+            (State::BufInner, Event::Text(ref e)) => {
+                return Ok(e.unescape_with(irods_unescapes)?.to_string());
+            }
+            (state, Event::Eof) => {
+                return Err(IrodsError::Other(format!(
+                    "unexpected EOF in state: [{:?}]",
+                    state
+                )))
+            }
+            (state, _) => state,
+        };
     }
 }
@@ -0,0 +1,57 @@
+use crate::bosd::xml::XMLDeserializable;
+use crate::bosd::xml::XMLSerializable;
+use crate::bosd::Deserializable;
+use crate::bosd::Serialiazable;
+use crate::error::errors::IrodsError;
+
+use super::bin_bytes_buf::{buf_from_xml, buf_to_xml};
+
+/// Plain-text counterpart of `BinBytesBuf`, used by the JSON APIs.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BytesBuf {
+    pub buf: String,
+}
+
+impl BytesBuf {
+    pub fn new(buf: &str) -> Self {
+        BytesBuf {
+            buf: String::from(buf),
+        }
+    }
+}
+
+impl Serialiazable for BytesBuf {}
+impl XMLSerializable for BytesBuf {
+    fn to_xml(&self, sink: &mut Vec<u8>) -> Result<usize, IrodsError> {
+        buf_to_xml("BytesBuf_PI", &self.buf, sink)
+    }
+}
+
+impl Deserializable for BytesBuf {}
+impl XMLDeserializable for BytesBuf {
+    fn from_xml(xml: &[u8]) -> Result<Self, IrodsError>
+    where
+        Self: Sized,
+    {
+        Ok(BytesBuf {
+            buf: buf_from_xml(b"BytesBuf_PI", xml)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bytes_buf_round_trip() {
+        let mut sink = Vec::new();
+        let len = BytesBuf::new(r#"{"fd":3}"#).to_xml(&mut sink).unwrap();
+
+        let xml = std::str::from_utf8(&sink[..len]).unwrap();
+        assert!(xml.starts_with("<BytesBuf_PI><buflen>8</buflen>"));
+
+        let parsed = BytesBuf::from_xml(&sink[..len]).unwrap();
+        assert_eq!(parsed, BytesBuf::new(r#"{"fd":3}"#));
+    }
+}
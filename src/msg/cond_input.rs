@@ -9,7 +9,7 @@ use quick_xml::{
 
 use crate::{bosd::xml::XMLSerializableChild, common::cond_input_kw::CondInputKw, tag, tag_fmt};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CondInput {
     kw_map: Vec<(CondInputKw, String)>,
 }
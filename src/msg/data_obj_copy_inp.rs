@@ -0,0 +1,40 @@
+use std::io::Cursor;
+
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+
+use crate::bosd::{
+    xml::{XMLSerializable, XMLSerializableChild},
+    Serialiazable,
+};
+
+use super::data_obj_inp::DataObjInp;
+
+/// A source and a destination, as taken by the copy and rename APIs.
+#[derive(Debug)]
+pub struct DataObjCopyInp {
+    pub src: DataObjInp,
+    pub dst: DataObjInp,
+}
+
+impl DataObjCopyInp {
+    pub fn new(src: DataObjInp, dst: DataObjInp) -> Self {
+        Self { src, dst }
+    }
+}
+
+impl Serialiazable for DataObjCopyInp {}
+impl XMLSerializable for DataObjCopyInp {
+    fn to_xml(&self, sink: &mut Vec<u8>) -> Result<usize, crate::error::errors::IrodsError> {
+        let mut cursor = Cursor::new(sink);
+        let mut writer = quick_xml::Writer::new(&mut cursor);
+
+        writer.write_event(Event::Start(BytesStart::new("DataObjCopyInp_PI")))?;
+
+        self.src.to_nested_xml(&mut writer)?;
+        self.dst.to_nested_xml(&mut writer)?;
+
+        writer.write_event(Event::End(BytesEnd::new("DataObjCopyInp_PI")))?;
+
+        Ok(cursor.position() as usize)
+    }
+}
@@ -20,7 +20,7 @@ pub struct DataObjInp {
     open_flags: i32,
     pub opr_type: OprType,
     pub offset: i64,
-    pub data_size: i32,
+    pub data_size: i64,
     pub num_threads: i32,
     pub spec_coll: Option<SpecialCollection>,
     pub cond_input: CondInput,
@@ -48,6 +48,17 @@ impl XMLSerializable for DataObjInp {
         let mut cursor = Cursor::new(sink);
         let mut writer = quick_xml::Writer::new(&mut cursor);
 
+        self.to_nested_xml(&mut writer)?;
+
+        Ok(cursor.position() as usize)
+    }
+}
+
+impl XMLSerializableChild for DataObjInp {
+    fn to_nested_xml<'r, 't1, 't2>(
+        &self,
+        writer: &'r mut quick_xml::Writer<&'t1 mut Cursor<&'t2 mut Vec<u8>>>,
+    ) -> Result<(), crate::error::errors::IrodsError> {
         writer.write_event(Event::Start(BytesStart::new("DataObjInp_PI")))?;
 
         tag!(writer, "objPath", &self.path);
@@ -59,13 +70,13 @@ impl XMLSerializable for DataObjInp {
         tag_fmt!(writer, "oprType", "{}", self.opr_type as i32);
 
         if let Some(ref spec_coll) = self.spec_coll {
-            spec_coll.to_nested_xml(&mut writer)?;
+            spec_coll.to_nested_xml(writer)?;
         }
 
-        self.cond_input.to_nested_xml(&mut writer)?;
+        self.cond_input.to_nested_xml(writer)?;
 
         writer.write_event(Event::End(BytesEnd::new("DataObjInp_PI")))?;
 
-        Ok(cursor.position() as usize)
+        Ok(())
     }
 }
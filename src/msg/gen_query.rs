@@ -4,6 +4,7 @@ use std::{
 };
 
 use quick_xml::{
+    escape::escape_with,
     events::{BytesEnd, BytesStart, Event},
     Reader, Writer,
 };
@@ -23,19 +24,182 @@ use crate::{
 
 use super::cond_input::CondInput;
 
-#[derive(Debug)]
+/// How a column compares to a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn operator(self) -> &'static str {
+        match self {
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "<>",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+        }
+    }
+
+    fn negated(self) -> Self {
+        match self {
+            Comparison::Equal => Comparison::NotEqual,
+            Comparison::NotEqual => Comparison::Equal,
+            Comparison::Less => Comparison::GreaterOrEqual,
+            Comparison::LessOrEqual => Comparison::Greater,
+            Comparison::Greater => Comparison::LessOrEqual,
+            Comparison::GreaterOrEqual => Comparison::Less,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum IcatPredicate {
     Equals(String),
+    NotEquals(String),
+    /// `%` matches any run of characters and `_` any one character.
+    Like(String),
+    NotLike(String),
+    In(Vec<String>),
+    /// Both bounds are included.
+    Between(String, String),
+    LessThan(String),
+    LessOrEqual(String),
+    GreaterThan(String),
+    GreaterOrEqual(String),
+    /// Compares the column as a number. Catalog values such as AVU values
+    /// are strings, which would otherwise be compared character by
+    /// character.
+    Numeric(Comparison, f64),
+    /// Met if any of the predicates is.
+    Or(Vec<IcatPredicate>),
+    /// Met if the predicate is not. GenQuery has no general negation, so
+    /// this is rewritten into the opposite operator and fails to serialize
+    /// for `In`, `Between` and `Or`, which have none.
+    Not(Box<IcatPredicate>),
+}
+
+// The server takes everything between two single quotes as the value and
+// has no way of escaping one inside it
+fn quote(value: &str) -> Result<String, IrodsError> {
+    if value.contains('\'') {
+        return Err(IrodsError::Other(format!(
+            "Query value cannot contain a single quote: {value}"
+        )));
+    }
+
+    Ok(format!("'{value}'"))
+}
+
+impl IcatPredicate {
+    // The condition as the server parses it, before escaping for XML
+    fn to_condition(&self) -> Result<String, IrodsError> {
+        Ok(match self {
+            IcatPredicate::Equals(value) => format!("= {}", quote(value)?),
+            IcatPredicate::NotEquals(value) => format!("<> {}", quote(value)?),
+            IcatPredicate::Like(pattern) => format!("like {}", quote(pattern)?),
+            IcatPredicate::NotLike(pattern) => format!("not like {}", quote(pattern)?),
+            IcatPredicate::In(values) => {
+                if values.is_empty() {
+                    return Err(IrodsError::Other("Empty list of query values".into()));
+                }
+
+                let values = values
+                    .iter()
+                    .map(|value| quote(value))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                format!("in ({})", values.join(", "))
+            }
+            IcatPredicate::Between(low, high) => {
+                format!("between {} {}", quote(low)?, quote(high)?)
+            }
+            IcatPredicate::LessThan(value) => format!("< {}", quote(value)?),
+            IcatPredicate::LessOrEqual(value) => format!("<= {}", quote(value)?),
+            IcatPredicate::GreaterThan(value) => format!("> {}", quote(value)?),
+            IcatPredicate::GreaterOrEqual(value) => format!(">= {}", quote(value)?),
+            IcatPredicate::Numeric(comparison, value) => {
+                format!("{} {value}", comparison.operator())
+            }
+            IcatPredicate::Or(alternatives) => {
+                if alternatives.is_empty() {
+                    return Err(IrodsError::Other("Empty list of alternatives".into()));
+                }
+
+                alternatives
+                    .iter()
+                    .map(IcatPredicate::to_condition)
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join(" || ")
+            }
+            IcatPredicate::Not(predicate) => predicate.negated()?.to_condition()?,
+        })
+    }
+
+    fn negated(&self) -> Result<IcatPredicate, IrodsError> {
+        Ok(match self {
+            IcatPredicate::Equals(value) => IcatPredicate::NotEquals(value.clone()),
+            IcatPredicate::NotEquals(value) => IcatPredicate::Equals(value.clone()),
+            IcatPredicate::Like(pattern) => IcatPredicate::NotLike(pattern.clone()),
+            IcatPredicate::NotLike(pattern) => IcatPredicate::Like(pattern.clone()),
+            IcatPredicate::LessThan(value) => IcatPredicate::GreaterOrEqual(value.clone()),
+            IcatPredicate::LessOrEqual(value) => IcatPredicate::GreaterThan(value.clone()),
+            IcatPredicate::GreaterThan(value) => IcatPredicate::LessOrEqual(value.clone()),
+            IcatPredicate::GreaterOrEqual(value) => IcatPredicate::LessThan(value.clone()),
+            IcatPredicate::Numeric(comparison, value) => {
+                IcatPredicate::Numeric(comparison.negated(), *value)
+            }
+            IcatPredicate::Not(predicate) => (**predicate).clone(),
+            IcatPredicate::In(_) | IcatPredicate::Between(..) | IcatPredicate::Or(_) => {
+                return Err(IrodsError::Other(format!(
+                    "GenQuery cannot negate {self:?}"
+                )))
+            }
+        })
+    }
 }
 
-#[derive(Debug)]
+/// Computes one value over the matching rows of a column, grouping by the
+/// other selected columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Min = 2,
+    Max = 3,
+    Sum = 4,
+    Avg = 5,
+    Count = 6,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Ascending = 0x400,
+    Descending = 0x800,
+}
+
+// Select value of a column returned as is
+const SELECT_NORMAL: u32 = 1;
+
+// Bits of the query options
+pub(crate) const RETURN_TOTAL_ROW_COUNT: u32 = 0x20;
+const NO_DISTINCT: u32 = 0x40;
+pub(crate) const AUTO_CLOSE: u32 = 0x100;
+
+#[derive(Debug, Clone)]
 pub struct GenQueryInp {
     pub max_rows: u32,
     pub continue_index: usize,
     pub partial_start_inx: usize,
     pub flags: u32,
     pub options: CondInput,
-    pub selects: Vec<IcatColumn>,
+    // Each column with its select value, which holds the aggregate and the
+    // order. Set through `QueryBuilder` so the bits stay consistent.
+    selects: Vec<(IcatColumn, u32)>,
     pub conditions: Vec<(IcatColumn, IcatPredicate)>,
 }
 
@@ -56,11 +220,11 @@ impl XMLSerializable for GenQueryInp {
 
         writer.write_event(Event::Start(BytesStart::new("InxIvalPair_PI")))?;
         tag_fmt!(writer, "iiLen", "{}", self.selects.len());
-        for column in &self.selects {
+        for (column, _) in &self.selects {
             tag_fmt!(writer, "inx", "{}", *column as u32);
         }
-        for _ in 0..self.selects.len() {
-            tag_fmt!(writer, "ivalue", "{}", "1");
+        for (_, value) in &self.selects {
+            tag_fmt!(writer, "ivalue", "{}", value);
         }
         writer.write_event(Event::End(BytesEnd::new("InxIvalPair_PI")))?;
 
@@ -70,11 +234,12 @@ impl XMLSerializable for GenQueryInp {
             tag_fmt!(writer, "inx", "{}", *column as u32);
         }
         for (_, predicate) in &self.conditions {
-            match predicate {
-                IcatPredicate::Equals(value) => {
-                    tag_fmt!(writer, "svalue", "= &apos;{}&apos;", value);
-                }
-            }
+            tag_fmt!(
+                writer,
+                "svalue",
+                "{}",
+                escape_with(&predicate.to_condition()?, irods_escapes)
+            );
         }
         writer.write_event(Event::End(BytesEnd::new("InxValPair_PI")))?;
 
@@ -102,6 +267,11 @@ impl GenQueryInp {
     fn builder() -> QueryBuilder {
         QueryBuilder::new()
     }
+
+    /// The selected columns, in the order their values are returned.
+    pub fn columns(&self) -> impl Iterator<Item = IcatColumn> + '_ {
+        self.selects.iter().map(|(column, _)| *column)
+    }
 }
 
 pub struct QueryBuilder {
@@ -141,15 +311,114 @@ impl QueryBuilder {
     }
 
     pub fn select(mut self, column: IcatColumn) -> Self {
-        self.query.selects.push(column);
+        self.query.selects.push((column, SELECT_NORMAL));
+        self
+    }
+
+    pub fn select_aggregate(mut self, column: IcatColumn, aggregate: Aggregate) -> Self {
+        self.query.selects.push((column, aggregate as u32));
+        self
+    }
+
+    /// Sorts the rows by `column`, selecting it if it is not already. Rows
+    /// are sorted by the ordered columns in the order they are selected.
+    pub fn order_by(mut self, column: IcatColumn, order: Order) -> Self {
+        match self.query.selects.iter_mut().find(|(c, _)| *c == column) {
+            Some((_, value)) => *value |= order as u32,
+            None => self
+                .query
+                .selects
+                .push((column, SELECT_NORMAL | order as u32)),
+        }
         self
     }
 
+    /// Returns every matching row rather than only distinct ones.
+    pub fn no_distinct(mut self) -> Self {
+        self.query.flags |= NO_DISTINCT;
+        self
+    }
+
+    /// Has the server count every matching row into `total_row_count`,
+    /// not only those of the first page.
+    pub fn return_total_row_count(mut self) -> Self {
+        self.query.flags |= RETURN_TOTAL_ROW_COUNT;
+        self
+    }
+
+    /// Has the server free the query after the first page, for queries
+    /// whose further pages are never read.
+    pub fn auto_close(mut self) -> Self {
+        self.query.flags |= AUTO_CLOSE;
+        self
+    }
+
+    /// Queries the catalog of another zone.
+    pub fn zone(self, zone: &str) -> Self {
+        self.kw(CondInputKw::ZoneKw, zone.to_owned())
+    }
+
     pub fn condition(mut self, column: IcatColumn, predicate: IcatPredicate) -> Self {
         self.query.conditions.push((column, predicate));
         self
     }
 
+    pub fn equals(self, column: IcatColumn, value: &str) -> Self {
+        self.condition(column, IcatPredicate::Equals(value.to_owned()))
+    }
+
+    pub fn not_equals(self, column: IcatColumn, value: &str) -> Self {
+        self.condition(column, IcatPredicate::NotEquals(value.to_owned()))
+    }
+
+    pub fn like(self, column: IcatColumn, pattern: &str) -> Self {
+        self.condition(column, IcatPredicate::Like(pattern.to_owned()))
+    }
+
+    pub fn not_like(self, column: IcatColumn, pattern: &str) -> Self {
+        self.condition(column, IcatPredicate::NotLike(pattern.to_owned()))
+    }
+
+    pub fn in_values<S: AsRef<str>>(self, column: IcatColumn, values: &[S]) -> Self {
+        let values = values.iter().map(|v| v.as_ref().to_owned()).collect();
+        self.condition(column, IcatPredicate::In(values))
+    }
+
+    pub fn between(self, column: IcatColumn, low: &str, high: &str) -> Self {
+        self.condition(
+            column,
+            IcatPredicate::Between(low.to_owned(), high.to_owned()),
+        )
+    }
+
+    pub fn less_than(self, column: IcatColumn, value: &str) -> Self {
+        self.condition(column, IcatPredicate::LessThan(value.to_owned()))
+    }
+
+    pub fn less_or_equal(self, column: IcatColumn, value: &str) -> Self {
+        self.condition(column, IcatPredicate::LessOrEqual(value.to_owned()))
+    }
+
+    pub fn greater_than(self, column: IcatColumn, value: &str) -> Self {
+        self.condition(column, IcatPredicate::GreaterThan(value.to_owned()))
+    }
+
+    pub fn greater_or_equal(self, column: IcatColumn, value: &str) -> Self {
+        self.condition(column, IcatPredicate::GreaterOrEqual(value.to_owned()))
+    }
+
+    pub fn numeric(self, column: IcatColumn, comparison: Comparison, value: f64) -> Self {
+        self.condition(column, IcatPredicate::Numeric(comparison, value))
+    }
+
+    pub fn any_of(self, column: IcatColumn, alternatives: Vec<IcatPredicate>) -> Self {
+        self.condition(column, IcatPredicate::Or(alternatives))
+    }
+
+    pub fn not(self, column: IcatColumn, predicate: IcatPredicate) -> Self {
+        self.condition(column, IcatPredicate::Not(Box::new(predicate)))
+    }
+
     pub fn build(self) -> GenQueryInp {
         self.query
     }
@@ -339,3 +608,108 @@ impl XMLDeserializable for GenQueryOut {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn conditions_are_escaped() {
+        let inp = QueryBuilder::new()
+            .select(IcatColumn::DataObjectBaseName)
+            .condition(
+                IcatColumn::MetadataAttributeName,
+                IcatPredicate::Like("run%".into()),
+            )
+            .condition(
+                IcatColumn::MetadataAttributeValue,
+                IcatPredicate::Numeric(Comparison::Greater, 100.0),
+            )
+            .build();
+
+        let mut sink = Vec::new();
+        inp.to_xml(&mut sink).unwrap();
+        let xml = String::from_utf8(sink).unwrap();
+
+        assert!(xml.contains("<svalue>like &apos;run%&apos;</svalue>"));
+        assert!(xml.contains("<svalue>&gt; 100</svalue>"));
+    }
+
+    #[test]
+    fn compound_conditions() {
+        let predicate = IcatPredicate::Or(vec![
+            IcatPredicate::In(vec!["a".into(), "b".into()]),
+            IcatPredicate::Between("1".into(), "9".into()),
+            IcatPredicate::NotLike("tmp%".into()),
+        ]);
+
+        assert_eq!(
+            predicate.to_condition().unwrap(),
+            "in ('a', 'b') || between '1' '9' || not like 'tmp%'"
+        );
+
+        assert!(IcatPredicate::Equals("it's".into()).to_condition().is_err());
+        assert!(IcatPredicate::In(Vec::new()).to_condition().is_err());
+    }
+
+    #[test]
+    fn negated_conditions() {
+        let not = |predicate| IcatPredicate::Not(Box::new(predicate));
+
+        assert_eq!(
+            not(IcatPredicate::Like("tmp%".into()))
+                .to_condition()
+                .unwrap(),
+            "not like 'tmp%'"
+        );
+        assert_eq!(
+            not(IcatPredicate::LessThan("5".into()))
+                .to_condition()
+                .unwrap(),
+            ">= '5'"
+        );
+        assert_eq!(
+            not(IcatPredicate::Numeric(Comparison::Greater, 100.0))
+                .to_condition()
+                .unwrap(),
+            "<= 100"
+        );
+        assert_eq!(
+            not(not(IcatPredicate::Equals("a".into())))
+                .to_condition()
+                .unwrap(),
+            "= 'a'"
+        );
+
+        assert!(not(IcatPredicate::In(vec!["a".into()]))
+            .to_condition()
+            .is_err());
+        assert!(not(IcatPredicate::Between("1".into(), "9".into()))
+            .to_condition()
+            .is_err());
+        assert!(
+            not(IcatPredicate::Or(vec![IcatPredicate::Equals("a".into())]))
+                .to_condition()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn select_values() {
+        let inp = QueryBuilder::new()
+            .select(IcatColumn::CollectionName)
+            .select_aggregate(IcatColumn::DataObjectSize, Aggregate::Sum)
+            .order_by(IcatColumn::CollectionName, Order::Descending)
+            .order_by(IcatColumn::DataObjectBaseName, Order::Ascending)
+            .build();
+
+        assert_eq!(
+            inp.selects,
+            vec![
+                (IcatColumn::CollectionName, 0x801),
+                (IcatColumn::DataObjectSize, 4),
+                (IcatColumn::DataObjectBaseName, 0x401),
+            ]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use std::io::{Cursor, Write};
+
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+
+use crate::{
+    bosd::{xml::XMLSerializable, Serialiazable},
+    error::errors::IrodsError,
+    tag, tag_fmt,
+};
+
+#[derive(Debug)]
+pub struct Genquery2Inp {
+    pub query_string: String,
+    /// The zone whose catalog is queried, the local one if unset.
+    pub zone: Option<String>,
+    /// Returns the SQL the query translates to instead of running it.
+    pub sql_only: bool,
+    pub column_mappings: bool,
+}
+
+impl Genquery2Inp {
+    pub fn new(query_string: String, zone: Option<String>, sql_only: bool) -> Self {
+        Self {
+            query_string,
+            zone,
+            sql_only,
+            column_mappings: false,
+        }
+    }
+}
+
+impl Serialiazable for Genquery2Inp {}
+impl XMLSerializable for Genquery2Inp {
+    fn to_xml(&self, sink: &mut Vec<u8>) -> Result<usize, IrodsError> {
+        let mut cursor = Cursor::new(sink);
+        let mut writer = quick_xml::Writer::new(&mut cursor);
+
+        writer.write_event(Event::Start(BytesStart::new("Genquery2Input_PI")))?;
+
+        tag!(writer, "query_string", &self.query_string);
+        // A null pointer on the server's side
+        if let Some(zone) = &self.zone {
+            tag!(writer, "zone", zone);
+        }
+        tag_fmt!(writer, "sql_only", "{}", self.sql_only as i32);
+        tag_fmt!(writer, "column_mappings", "{}", self.column_mappings as i32);
+
+        writer.write_event(Event::End(BytesEnd::new("Genquery2Input_PI")))?;
+
+        Ok(cursor.position() as usize)
+    }
+}
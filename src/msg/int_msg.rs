@@ -0,0 +1,36 @@
+use std::io::{Cursor, Write};
+
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+
+use crate::{
+    bosd::{xml::XMLSerializable, Serialiazable},
+    tag_fmt,
+};
+
+/// A bare integer argument, e.g., the descriptor passed to `OprComplete`.
+#[derive(Debug)]
+pub struct IntMsg {
+    pub my_int: i32,
+}
+
+impl IntMsg {
+    pub fn new(my_int: i32) -> Self {
+        Self { my_int }
+    }
+}
+
+impl Serialiazable for IntMsg {}
+impl XMLSerializable for IntMsg {
+    fn to_xml(&self, sink: &mut Vec<u8>) -> Result<usize, crate::error::errors::IrodsError> {
+        let mut cursor = Cursor::new(sink);
+        let mut writer = quick_xml::Writer::new(&mut cursor);
+
+        writer.write_event(Event::Start(BytesStart::new("INT_PI")))?;
+
+        tag_fmt!(writer, "myInt", "{}", self.my_int);
+
+        writer.write_event(Event::End(BytesEnd::new("INT_PI")))?;
+
+        Ok(cursor.position() as usize)
+    }
+}
@@ -11,16 +11,16 @@ use crate::{
     tag, AVUOperation, AVUTarget, AVU,
 };
 
+/// The arguments of an `imeta` subcommand. Which argument means what
+/// depends on the operation, so they are kept as the server sees them.
 #[derive(Debug)]
 pub struct ModAVURequest {
-    op: AVUOperation,
-    target_type: AVUTarget,
-    target_name: String,
-    avu: AVU,
-    new_avu: Option<AVU>,
+    args: Vec<String>,
 }
 
 impl ModAVURequest {
+    /// For every operation that names one target and an AVU. `new_avu` is
+    /// only used by `Modify`, where its empty fields are left unchanged.
     pub fn new(
         op: AVUOperation,
         target_type: AVUTarget,
@@ -28,12 +28,56 @@ impl ModAVURequest {
         avu: AVU,
         new_avu: Option<AVU>,
     ) -> Self {
-        Self {
-            op,
-            target_type,
+        let mut args = vec![
+            Into::<&str>::into(op).to_owned(),
+            Into::<&str>::into(target_type).to_owned(),
             target_name,
-            avu,
-            new_avu,
+            avu.attribute,
+            avu.value,
+            avu.unit,
+        ];
+
+        if let Some(new_avu) = new_avu {
+            for (prefix, new) in [
+                ("n:", new_avu.attribute),
+                ("v:", new_avu.value),
+                ("u:", new_avu.unit),
+            ] {
+                if !new.is_empty() {
+                    args.push(format!("{prefix}{new}"));
+                }
+            }
+        }
+
+        Self { args }
+    }
+
+    pub fn remove_by_id(target_type: AVUTarget, target_name: String, id: i64) -> Self {
+        Self {
+            args: vec![
+                Into::<&str>::into(AVUOperation::RemoveById).to_owned(),
+                Into::<&str>::into(target_type).to_owned(),
+                target_name,
+                id.to_string(),
+            ],
+        }
+    }
+
+    /// Copies every AVU of one target to another, possibly of another type.
+    pub fn copy(
+        src_type: AVUTarget,
+        dst_type: AVUTarget,
+        src_name: String,
+        dst_name: String,
+    ) -> Self {
+        Self {
+            args: vec![
+                Into::<&str>::into(AVUOperation::Copy).to_owned(),
+                Into::<&str>::into(src_type).to_owned(),
+                Into::<&str>::into(dst_type).to_owned(),
+                src_name,
+                dst_name,
+            ],
         }
     }
 }
@@ -47,28 +91,36 @@ impl XMLSerializable for ModAVURequest {
 
         writer.write_event(Event::Start(BytesStart::new("ModAVUMetaDataInp_PI")))?;
 
-        tag!(writer, "arg0", self.op.into());
-        tag!(writer, "arg1", self.target_type.into());
-        tag!(writer, "arg2", self.target_name.as_str());
-        tag!(writer, "arg3", self.avu.attribute.as_str());
-        tag!(writer, "arg4", self.avu.value.as_str());
-        tag!(writer, "arg5", self.avu.unit.as_str());
-        match &self.new_avu {
-            Some(new_avu) => {
-                tag!(writer, "arg6", new_avu.attribute.as_str());
-                tag!(writer, "arg7", new_avu.value.as_str());
-                tag!(writer, "arg8", new_avu.unit.as_str());
-            }
-            None => {
-                tag!(writer, "arg6", "");
-                tag!(writer, "arg7", "");
-                tag!(writer, "arg8", "");
-            }
-        };
-        tag!(writer, "arg9", "");
+        for i in 0..10 {
+            let arg = self.args.get(i).map(String::as_str).unwrap_or_default();
+            tag!(writer, &format!("arg{i}"), arg);
+        }
 
         writer.write_event(Event::End(BytesEnd::new("ModAVUMetaDataInp_PI")))?;
 
         Ok(cursor.position() as usize)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn modify_prefixes_new_fields() {
+        let inp = ModAVURequest::new(
+            AVUOperation::Modify,
+            AVUTarget::DataObject,
+            "/tempZone/home/rods/a.txt".into(),
+            AVU::new("size", "10", "cm"),
+            Some(AVU::new("", "12", "")),
+        );
+
+        assert_eq!(
+            inp.args[..4],
+            ["mod", "-d", "/tempZone/home/rods/a.txt", "size"]
+        );
+        assert_eq!(inp.args[6], "v:12");
+        assert_eq!(inp.args.len(), 7);
+    }
+}
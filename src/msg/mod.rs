@@ -1,16 +1,24 @@
 pub mod acls;
 pub mod admin;
 pub mod bin_bytes_buf;
+pub mod bytes_buf;
 pub mod coll;
 pub mod cond_input;
 pub mod cs_neg;
+pub mod data_obj_copy_inp;
 pub mod data_obj_inp;
 pub mod file_lseek_out;
 pub mod gen_query;
+pub mod genquery2;
 pub mod header;
+pub mod int_msg;
 pub mod meta;
+pub mod mod_data_obj_meta;
 pub mod opened_data_obj_inp;
+pub mod portal_opr_out;
 pub mod spec_coll;
 pub mod startup_pack;
 pub mod stat;
+pub mod str_msg;
+pub mod struct_file_ext_and_reg_inp;
 pub mod version;
@@ -0,0 +1,86 @@
+use std::io::{Cursor, Write};
+
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+
+use crate::{
+    bosd::{
+        xml::{XMLSerializable, XMLSerializableChild},
+        Serialiazable,
+    },
+    tag, tag_fmt,
+};
+
+use super::cond_input::CondInput;
+
+/// Input of `ModDataObjMeta`. Of the `DataObjInfo` it carries, the server only
+/// looks at the path and replica number when told which replica to change;
+/// the new values go in `reg_param`.
+#[derive(Debug)]
+pub struct ModDataObjMetaInp {
+    pub path: String,
+    pub replica_number: i32,
+    pub reg_param: CondInput,
+}
+
+impl ModDataObjMetaInp {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            replica_number: 0,
+            reg_param: CondInput::new(),
+        }
+    }
+}
+
+impl Serialiazable for ModDataObjMetaInp {}
+impl XMLSerializable for ModDataObjMetaInp {
+    fn to_xml(&self, sink: &mut Vec<u8>) -> Result<usize, crate::error::errors::IrodsError> {
+        let mut cursor = Cursor::new(sink);
+        let mut writer = quick_xml::Writer::new(&mut cursor);
+
+        writer.write_event(Event::Start(BytesStart::new("ModDataObjMeta_PI")))?;
+        writer.write_event(Event::Start(BytesStart::new("DataObjInfo_PI")))?;
+
+        tag!(writer, "objPath", &self.path);
+        tag!(writer, "rescName", "");
+        tag!(writer, "rescHier", "");
+        tag!(writer, "dataType", "");
+        tag_fmt!(writer, "dataSize", "{}", 0);
+        tag!(writer, "chksum", "");
+        tag!(writer, "version", "");
+        tag!(writer, "filePath", "");
+        tag!(writer, "dataOwnerName", "");
+        tag!(writer, "dataOwnerZone", "");
+        tag_fmt!(writer, "replNum", "{}", self.replica_number);
+        tag_fmt!(writer, "replStatus", "{}", 0);
+        tag!(writer, "statusString", "");
+        tag_fmt!(writer, "dataId", "{}", 0);
+        tag_fmt!(writer, "collId", "{}", 0);
+        tag_fmt!(writer, "dataMapId", "{}", 0);
+        tag_fmt!(writer, "flags", "{}", 0);
+        tag!(writer, "dataComments", "");
+        tag!(writer, "dataMode", "");
+        tag!(writer, "dataExpiry", "");
+        tag!(writer, "dataCreate", "");
+        tag!(writer, "dataModify", "");
+        tag!(writer, "dataAccess", "");
+        tag_fmt!(writer, "dataAccessInx", "{}", 0);
+        tag_fmt!(writer, "writeFlag", "{}", 0);
+        tag!(writer, "destRescName", "");
+        tag!(writer, "backupRescName", "");
+        tag!(writer, "subPath", "");
+        tag_fmt!(writer, "regUid", "{}", 0);
+        tag_fmt!(writer, "otherFlags", "{}", 0);
+        CondInput::new().to_nested_xml(&mut writer)?;
+        tag!(writer, "in_pdmo", "");
+        tag_fmt!(writer, "rescId", "{}", 0);
+
+        writer.write_event(Event::End(BytesEnd::new("DataObjInfo_PI")))?;
+
+        self.reg_param.to_nested_xml(&mut writer)?;
+
+        writer.write_event(Event::End(BytesEnd::new("ModDataObjMeta_PI")))?;
+
+        Ok(cursor.position() as usize)
+    }
+}
@@ -0,0 +1,185 @@
+use crate::bosd::xml::irods_unescapes;
+use crate::error::errors::IrodsError;
+use quick_xml::{events::Event, Reader};
+
+use crate::bosd::{xml::XMLDeserializable, Deserializable};
+
+/*
+<PortalOprOut_PI>
+<status>0</status>
+<l1descInx>3</l1descInx>
+<numThreads>0</numThreads>
+<chksum></chksum>
+<PortList_PI>
+<portNum>0</portNum>
+<cookie>0</cookie>
+<sock>0</sock>
+<windowSize>0</windowSize>
+<hostAddr></hostAddr>
+</PortList_PI>
+</PortalOprOut_PI>
+*/
+
+#[derive(Debug)]
+pub struct PortalOprOut {
+    pub status: i32,
+    pub l1_desc_inx: i32,
+    pub num_threads: i32,
+    pub checksum: Option<String>,
+    pub port_list: PortList,
+}
+
+#[derive(Debug)]
+pub struct PortList {
+    pub port_num: u16,
+    pub cookie: i32,
+    pub sock: i32,
+    pub window_size: i32,
+    pub host_addr: String,
+}
+
+impl Deserializable for PortalOprOut {}
+impl XMLDeserializable for PortalOprOut {
+    fn from_xml(xml: &[u8]) -> Result<Self, IrodsError>
+    where
+        Self: Sized,
+    {
+        #[repr(u8)]
+        enum State {
+            Tag,
+            Status,
+            StatusInner,
+            L1DescInx,
+            L1DescInxInner,
+            NumThreads,
+            NumThreadsInner,
+            Checksum,
+            ChecksumInner,
+            PortList,
+            PortNum,
+            PortNumInner,
+            Cookie,
+            CookieInner,
+            Sock,
+            SockInner,
+            WindowSize,
+            WindowSizeInner,
+            HostAddr,
+            HostAddrInner,
+        }
+
+        let mut status: Option<i32> = None;
+        let mut l1_desc_inx: Option<i32> = None;
+        let mut num_threads: Option<i32> = None;
+        let mut checksum: Option<String> = None;
+        let mut port_num: Option<u16> = None;
+        let mut cookie: Option<i32> = None;
+        let mut sock: Option<i32> = None;
+        let mut window_size: Option<i32> = None;
+        let mut host_addr = String::new();
+
+        let mut reader = Reader::from_reader(xml);
+
+        let mut state = State::Tag;
+
+        loop {
+            state = match (state, reader.read_event()?) {
+                (State::Tag, Event::Start(e)) if e.name().as_ref() == b"PortalOprOut_PI" => {
+                    State::Status
+                }
+                (State::Status, Event::Start(e)) if e.name().as_ref() == b"status" => {
+                    State::StatusInner
+                }
+                (State::StatusInner, Event::Text(e)) => {
+                    status = Some(e.unescape_with(irods_unescapes)?.parse()?);
+                    State::L1DescInx
+                }
+                (State::L1DescInx, Event::Start(e)) if e.name().as_ref() == b"l1descInx" => {
+                    State::L1DescInxInner
+                }
+                (State::L1DescInxInner, Event::Text(e)) => {
+                    l1_desc_inx = Some(e.unescape_with(irods_unescapes)?.parse()?);
+                    State::NumThreads
+                }
+                (State::NumThreads, Event::Start(e)) if e.name().as_ref() == b"numThreads" => {
+                    State::NumThreadsInner
+                }
+                (State::NumThreadsInner, Event::Text(e)) => {
+                    num_threads = Some(e.unescape_with(irods_unescapes)?.parse()?);
+                    State::Checksum
+                }
+                (State::Checksum, Event::Empty(e)) if e.name().as_ref() == b"chksum" => {
+                    State::PortList
+                }
+                (State::Checksum, Event::Start(e)) if e.name().as_ref() == b"chksum" => {
+                    State::ChecksumInner
+                }
+                (State::ChecksumInner, Event::Text(e)) => {
+                    checksum = Some(e.unescape_with(irods_unescapes)?.to_string());
+                    State::PortList
+                }
+                (State::ChecksumInner, Event::End(e)) if e.name().as_ref() == b"chksum" => {
+                    State::PortList
+                }
+                (State::PortList, Event::Start(e)) if e.name().as_ref() == b"PortList_PI" => {
+                    State::PortNum
+                }
+                (State::PortNum, Event::Start(e)) if e.name().as_ref() == b"portNum" => {
+                    State::PortNumInner
+                }
+                (State::PortNumInner, Event::Text(e)) => {
+                    // The upper half can carry a UDP port, unused here
+                    let raw: i32 = e.unescape_with(irods_unescapes)?.parse()?;
+                    port_num = Some((raw & 0xffff) as u16);
+                    State::Cookie
+                }
+                (State::Cookie, Event::Start(e)) if e.name().as_ref() == b"cookie" => {
+                    State::CookieInner
+                }
+                (State::CookieInner, Event::Text(e)) => {
+                    cookie = Some(e.unescape_with(irods_unescapes)?.parse()?);
+                    State::Sock
+                }
+                (State::Sock, Event::Start(e)) if e.name().as_ref() == b"sock" => State::SockInner,
+                (State::SockInner, Event::Text(e)) => {
+                    sock = Some(e.unescape_with(irods_unescapes)?.parse()?);
+                    State::WindowSize
+                }
+                (State::WindowSize, Event::Start(e)) if e.name().as_ref() == b"windowSize" => {
+                    State::WindowSizeInner
+                }
+                (State::WindowSizeInner, Event::Text(e)) => {
+                    window_size = Some(e.unescape_with(irods_unescapes)?.parse()?);
+                    State::HostAddr
+                }
+                (State::HostAddr, Event::Empty(e)) if e.name().as_ref() == b"hostAddr" => break,
+                (State::HostAddr, Event::Start(e)) if e.name().as_ref() == b"hostAddr" => {
+                    State::HostAddrInner
+                }
+                (State::HostAddrInner, Event::Text(e)) => {
+                    host_addr = e.unescape_with(irods_unescapes)?.to_string();
+                    break;
+                }
+                (State::HostAddrInner, Event::End(e)) if e.name().as_ref() == b"hostAddr" => break,
+                (_, Event::Eof) => {
+                    return Err(IrodsError::Other("Unexpected EOF".into()));
+                }
+                state => state.0,
+            }
+        }
+
+        Ok(Self {
+            status: status.ok_or(IrodsError::Other("Missing status".into()))?,
+            l1_desc_inx: l1_desc_inx.ok_or(IrodsError::Other("Missing l1descInx".into()))?,
+            num_threads: num_threads.ok_or(IrodsError::Other("Missing numThreads".into()))?,
+            checksum,
+            port_list: PortList {
+                port_num: port_num.ok_or(IrodsError::Other("Missing portNum".into()))?,
+                cookie: cookie.ok_or(IrodsError::Other("Missing cookie".into()))?,
+                sock: sock.ok_or(IrodsError::Other("Missing sock".into()))?,
+                window_size: window_size.ok_or(IrodsError::Other("Missing windowSize".into()))?,
+                host_addr,
+            },
+        })
+    }
+}
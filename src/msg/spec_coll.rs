@@ -3,11 +3,14 @@ use std::io::{Cursor, Write};
 use crate::error::errors::IrodsError;
 use quick_xml::{
     events::{BytesEnd, BytesStart, BytesText, Event},
-    Writer,
+    Reader, Writer,
 };
 
 use crate::{
-    bosd::{xml::XMLSerializableChild, Serialiazable},
+    bosd::{
+        xml::{irods_unescapes, XMLSerializableChild},
+        Serialiazable,
+    },
     tag, tag_fmt,
 };
 
@@ -20,12 +23,43 @@ typedef enum SpecialCollClass {         /* class of SpecColl */
 } specCollClass_t;
 */
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SpecialCollectionClass {
-    NoSpecialCollection = 1,
-    StructFile = 2,
-    Mounted = 3,
-    Linked = 4,
+    NoSpecialCollection = 0,
+    StructFile = 1,
+    Mounted = 2,
+    Linked = 3,
+}
+
+impl TryFrom<i32> for SpecialCollectionClass {
+    type Error = IrodsError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(SpecialCollectionClass::NoSpecialCollection),
+            1 => Ok(SpecialCollectionClass::StructFile),
+            2 => Ok(SpecialCollectionClass::Mounted),
+            3 => Ok(SpecialCollectionClass::Linked),
+            _ => Err(IrodsError::Other(
+                "Invalid value for SpecialCollectionClass".into(),
+            )),
+        }
+    }
+}
+
+impl SpecialCollectionClass {
+    /// Maps the collection type recorded in the catalog. Ordinary
+    /// collections have none.
+    pub fn from_catalog_type(coll_type: &str) -> Option<Self> {
+        match coll_type {
+            "mountPoint" => Some(SpecialCollectionClass::Mounted),
+            "linkPoint" => Some(SpecialCollectionClass::Linked),
+            "tarStructFile" | "haawStructFile" | "mssoStructFile" => {
+                Some(SpecialCollectionClass::StructFile)
+            }
+            _ => None,
+        }
+    }
 }
 
 /*
@@ -37,7 +71,7 @@ typedef enum StructFileType {                /* structFile type */
 } structFileType_t;
 */
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StructFileType {
     No = 0,
     Haaw = 1,
@@ -45,18 +79,85 @@ pub enum StructFileType {
     Msso = 3,
 }
 
-#[derive(Debug)]
+impl TryFrom<i32> for StructFileType {
+    type Error = IrodsError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(StructFileType::No),
+            1 => Ok(StructFileType::Haaw),
+            2 => Ok(StructFileType::Tar),
+            3 => Ok(StructFileType::Msso),
+            _ => Err(IrodsError::Other("Invalid value for StructFileType".into())),
+        }
+    }
+}
+
+/// Where a mounted, linked or structured-file collection keeps its contents.
+/// `collection` is the special collection itself, which `obj_path` may lie
+/// anywhere under.
+#[derive(Debug, Clone)]
 pub struct SpecialCollection {
-    class: SpecialCollectionClass,
-    struct_file_type: StructFileType,
-    collection: String,
-    obj_path: String,
-    resource: String,
-    resc_hier: String,
-    phy_path: String,
-    cache_dir: String,
-    cache_dirty: bool,
-    repl_num: i32,
+    pub class: SpecialCollectionClass,
+    pub struct_file_type: StructFileType,
+    pub collection: String,
+    pub obj_path: String,
+    pub resource: String,
+    pub resc_hier: String,
+    pub phy_path: String,
+    pub cache_dir: String,
+    pub cache_dirty: bool,
+    pub repl_num: i32,
+}
+
+impl SpecialCollection {
+    /// Reads the fields of a `SpecColl_PI` whose start tag `reader` has just
+    /// consumed, up to and including its end tag.
+    pub(crate) fn from_nested_xml(reader: &mut Reader<&[u8]>) -> Result<Self, IrodsError> {
+        let mut spec_coll = SpecialCollection {
+            class: SpecialCollectionClass::NoSpecialCollection,
+            struct_file_type: StructFileType::No,
+            collection: String::new(),
+            obj_path: String::new(),
+            resource: String::new(),
+            resc_hier: String::new(),
+            phy_path: String::new(),
+            cache_dir: String::new(),
+            cache_dirty: false,
+            repl_num: 0,
+        };
+
+        // Empty strings come without a text event, so each field is set
+        // from whatever text follows its start tag
+        let mut field = Vec::new();
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => field = e.name().as_ref().to_vec(),
+                Event::Text(e) => {
+                    let text = e.unescape_with(irods_unescapes)?;
+
+                    match field.as_slice() {
+                        b"collClass" => spec_coll.class = text.parse::<i32>()?.try_into()?,
+                        b"type" => spec_coll.struct_file_type = text.parse::<i32>()?.try_into()?,
+                        b"collection" => spec_coll.collection = text.to_string(),
+                        b"objPath" => spec_coll.obj_path = text.to_string(),
+                        b"resource" => spec_coll.resource = text.to_string(),
+                        b"rescHier" => spec_coll.resc_hier = text.to_string(),
+                        b"phyPath" => spec_coll.phy_path = text.to_string(),
+                        b"cacheDir" => spec_coll.cache_dir = text.to_string(),
+                        b"cacheDirty" => spec_coll.cache_dirty = text.parse::<i32>()? != 0,
+                        b"replNum" => spec_coll.repl_num = text.parse()?,
+                        _ => {}
+                    }
+                }
+                Event::End(e) if e.name().as_ref() == b"SpecColl_PI" => return Ok(spec_coll),
+                Event::End(_) => field.clear(),
+                Event::Eof => return Err(IrodsError::Other("Unexpected EOF".into())),
+                _ => {}
+            }
+        }
+    }
 }
 
 impl XMLSerializableChild for SpecialCollection {
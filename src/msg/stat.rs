@@ -7,6 +7,8 @@ use crate::{
     common::ObjectType,
 };
 
+use super::spec_coll::SpecialCollection;
+
 #[derive(Debug)]
 pub struct RodsObjStat {
     pub size: usize,
@@ -18,6 +20,9 @@ pub struct RodsObjStat {
     pub owner_zone: String,
     pub create_time: u64,
     pub modify_time: u64,
+    /// Set for paths in or at a mounted, linked or structured-file
+    /// collection.
+    pub spec_coll: Option<SpecialCollection>,
 }
 
 impl Deserializable for RodsObjStat {}
@@ -47,6 +52,7 @@ impl XMLDeserializable for RodsObjStat {
             CreateTimeInner,
             ModifyTime,
             ModifyTimeInner,
+            SpecColl,
         }
 
         let mut size: Option<usize> = None;
@@ -58,6 +64,7 @@ impl XMLDeserializable for RodsObjStat {
         let mut owner_zone: Option<String> = None;
         let mut create_time: Option<u64> = None;
         let mut modify_time: Option<u64> = None;
+        let mut spec_coll: Option<SpecialCollection> = None;
 
         let mut reader = Reader::from_reader(xml);
 
@@ -142,28 +149,78 @@ impl XMLDeserializable for RodsObjStat {
                 }
                 (State::ModifyTimeInner, Event::Text(e)) => {
                     modify_time = Some(e.unescape_with(irods_unescapes)?.parse()?);
-                    return Ok(RodsObjStat {
-                        size: size.ok_or(IrodsError::Other("Missing size".into()))?,
-                        object_type: object_type
-                            .ok_or(IrodsError::Other("Missing object type".into()))?,
-                        mode: mode.ok_or(IrodsError::Other("Missing mode".into()))?,
-                        id: id.ok_or(IrodsError::Other("Missing id".into()))?,
-                        checksum,
-                        owner_name: owner_name
-                            .ok_or(IrodsError::Other("Missing owner name".into()))?,
-                        owner_zone: owner_zone
-                            .ok_or(IrodsError::Other("Missing owner zone".into()))?,
-                        create_time: create_time
-                            .ok_or(IrodsError::Other("Missing create time".into()))?,
-                        modify_time: modify_time
-                            .ok_or(IrodsError::Other("Missing modify time".into()))?,
-                    });
+                    State::SpecColl
+                }
+                // The special collection is only sent when there is one
+                (State::SpecColl, Event::Start(e)) if e.name().as_ref() == b"SpecColl_PI" => {
+                    spec_coll = Some(SpecialCollection::from_nested_xml(&mut reader)?);
+                    State::SpecColl
                 }
+                (State::SpecColl, Event::End(e)) if e.name().as_ref() == b"RodsObjStat_PI" => {
+                    break;
+                }
+                (State::SpecColl, Event::Eof) => break,
                 (_, Event::Eof) => {
                     return Err(IrodsError::Other("Unexpected EOF".into()));
                 }
                 state => state.0,
             }
         }
+
+        Ok(RodsObjStat {
+            size: size.ok_or(IrodsError::Other("Missing size".into()))?,
+            object_type: object_type.ok_or(IrodsError::Other("Missing object type".into()))?,
+            mode: mode.ok_or(IrodsError::Other("Missing mode".into()))?,
+            id: id.ok_or(IrodsError::Other("Missing id".into()))?,
+            checksum,
+            owner_name: owner_name.ok_or(IrodsError::Other("Missing owner name".into()))?,
+            owner_zone: owner_zone.ok_or(IrodsError::Other("Missing owner zone".into()))?,
+            create_time: create_time.ok_or(IrodsError::Other("Missing create time".into()))?,
+            modify_time: modify_time.ok_or(IrodsError::Other("Missing modify time".into()))?,
+            spec_coll,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::msg::spec_coll::SpecialCollectionClass;
+
+    use super::*;
+
+    #[test]
+    fn stat_of_mounted_collection() {
+        let xml = b"<RodsObjStat_PI>
+<objSize>0</objSize>
+<objType>2</objType>
+<dataMode>0</dataMode>
+<dataId>10015</dataId>
+<chksum/>
+<ownerName>rods</ownerName>
+<ownerZone>tempZone</ownerZone>
+<createTime>01700000000</createTime>
+<modifyTime>01700000000</modifyTime>
+<SpecColl_PI>
+<collClass>2</collClass>
+<type>0</type>
+<collection>/tempZone/home/rods/mnt</collection>
+<objPath>/tempZone/home/rods/mnt</objPath>
+<resource>demoResc</resource>
+<rescHier>demoResc</rescHier>
+<phyPath>/data/mnt</phyPath>
+<cacheDir></cacheDir>
+<cacheDirty>0</cacheDirty>
+<replNum>0</replNum>
+</SpecColl_PI>
+<rescHier>demoResc</rescHier>
+</RodsObjStat_PI>
+";
+
+        let stat = RodsObjStat::from_xml(xml).unwrap();
+        let spec_coll = stat.spec_coll.unwrap();
+
+        assert_eq!(spec_coll.class, SpecialCollectionClass::Mounted);
+        assert_eq!(spec_coll.phy_path, "/data/mnt");
+        assert_eq!(spec_coll.cache_dir, "");
     }
 }
@@ -0,0 +1,65 @@
+use crate::bosd::xml::irods_unescapes;
+use crate::error::errors::IrodsError;
+
+use crate::bosd::{xml::XMLDeserializable, Deserializable};
+
+/// A bare string reply, e.g., the host returned by `GetHostForGet`.
+#[derive(Debug)]
+pub struct StrMsg {
+    pub my_str: String,
+}
+
+impl Deserializable for StrMsg {}
+impl XMLDeserializable for StrMsg {
+    fn from_xml(xml: &[u8]) -> Result<Self, IrodsError>
+    where
+        Self: Sized,
+    {
+        #[repr(u8)]
+        enum State {
+            Tag,
+            MyStr,
+            MyStrInner,
+        }
+
+        let mut state = State::Tag;
+
+        let mut reader = quick_xml::Reader::from_reader(xml);
+
+        loop {
+            state = match (state, reader.read_event()?) {
+                (State::Tag, quick_xml::events::Event::Start(ref e))
+                    if e.name().as_ref() == b"STR_PI" =>
+                {
+                    State::MyStr
+                }
+                (State::MyStr, quick_xml::events::Event::Start(ref e))
+                    if e.name().as_ref() == b"myStr" =>
+                {
+                    State::MyStrInner
+                }
+                (State::MyStr, quick_xml::events::Event::Empty(ref e))
+                    if e.name().as_ref() == b"myStr" =>
+                {
+                    return Ok(Self {
+                        my_str: String::new(),
+                    });
+                }
+                (State::MyStrInner, quick_xml::events::Event::Text(e)) => {
+                    return Ok(Self {
+                        my_str: e.unescape_with(irods_unescapes)?.to_string(),
+                    });
+                }
+                (State::MyStrInner, quick_xml::events::Event::End(_)) => {
+                    return Ok(Self {
+                        my_str: String::new(),
+                    });
+                }
+                (_, quick_xml::events::Event::Eof) => {
+                    return Err(IrodsError::Other("Unexpected EOF".to_string()));
+                }
+                state => state.0,
+            };
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::io::{Cursor, Write};
+
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+
+use crate::{
+    bosd::{
+        xml::{XMLSerializable, XMLSerializableChild},
+        Serialiazable,
+    },
+    tag, tag_fmt,
+};
+
+use super::cond_input::CondInput;
+
+/// Input of both the bundle and the extract APIs: a structured file, e.g., a
+/// tar archive, and the collection it is made from or unpacked into.
+#[derive(Debug)]
+pub struct StructFileExtAndRegInp {
+    pub obj_path: String,
+    pub collection: String,
+    pub opr_type: i32,
+    pub flags: i32,
+    pub cond_input: CondInput,
+}
+
+impl StructFileExtAndRegInp {
+    pub fn new(obj_path: String, collection: String) -> Self {
+        Self {
+            obj_path,
+            collection,
+            opr_type: 0,
+            flags: 0,
+            cond_input: CondInput::new(),
+        }
+    }
+}
+
+impl Serialiazable for StructFileExtAndRegInp {}
+impl XMLSerializable for StructFileExtAndRegInp {
+    fn to_xml(&self, sink: &mut Vec<u8>) -> Result<usize, crate::error::errors::IrodsError> {
+        let mut cursor = Cursor::new(sink);
+        let mut writer = quick_xml::Writer::new(&mut cursor);
+
+        writer.write_event(Event::Start(BytesStart::new("StructFileExtAndRegInp_PI")))?;
+
+        tag!(writer, "objPath", &self.obj_path);
+        tag!(writer, "collection", &self.collection);
+        tag_fmt!(writer, "oprType", "{}", self.opr_type);
+        tag_fmt!(writer, "flags", "{}", self.flags);
+
+        self.cond_input.to_nested_xml(&mut writer)?;
+
+        writer.write_event(Event::End(BytesEnd::new("StructFileExtAndRegInp_PI")))?;
+
+        Ok(cursor.position() as usize)
+    }
+}
@@ -1,24 +1,374 @@
 mod test_common;
-use std::path::Path;
+use std::{path::PathBuf, time::Duration};
 
 use deadpool::managed;
+use futures::TryStreamExt;
 use irods_client::{
     bosd::xml::XML,
+    common::AccessLevel,
     connection::{authenticate::NativeAuthenticator, pool::IrodsManager, tcp::TcpConnector},
-    fs::{download::ParallelDownloadContext, upload::ParallelTransferContext},
+    error::errors::IrodsError,
+    fs::{
+        delete::DeleteRequest, download::ParallelDownloadContext, transfer::RetryPolicy,
+        upload::ParallelTransferContext,
+    },
+    msg::gen_query::{Comparison, IcatPredicate},
+    AVUTarget, AVU,
 };
 use test_common::test_manager;
 
 #[tokio::test]
 async fn gen_query_test() {
-    let mut pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 17);
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 17);
 
     let remote_path = "/tempZone/home/rods/totc.txt";
     let local_path = "./totc.txt";
 
-    ParallelDownloadContext::new(&mut pool, 10, Path::new(remote_path), Path::new(local_path))
-        .max_size_before_parallel(1024)
-        .download()
+    ParallelDownloadContext::new(
+        pool.clone(),
+        10,
+        PathBuf::from(remote_path),
+        PathBuf::from(local_path),
+    )
+    .max_size_before_parallel(1024)
+    .download()
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn spawned_download_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 17);
+
+    let handles: Vec<_> = ["./totc_a.txt", "./totc_b.txt"]
+        .into_iter()
+        .map(|local_path| {
+            ParallelDownloadContext::new(
+                pool.clone(),
+                4,
+                PathBuf::from("/tempZone/home/rods/totc.txt"),
+                PathBuf::from(local_path),
+            )
+            .spawn()
+        })
+        .collect();
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn redirected_download_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 17);
+
+    ParallelDownloadContext::new(
+        pool,
+        4,
+        PathBuf::from("/tempZone/home/rods/totc.txt"),
+        PathBuf::from("./totc_redirected.txt"),
+    )
+    .max_size_before_parallel(0)
+    .redirect_to_resource_server()
+    .download()
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn portal_download_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 17);
+
+    ParallelDownloadContext::new(
+        pool,
+        4,
+        PathBuf::from("/tempZone/home/rods/totc.txt"),
+        PathBuf::from("./totc_portal.txt"),
+    )
+    .max_size_before_parallel(0)
+    .use_portal()
+    .download()
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn copy_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 1);
+    let mut conn = pool.get().await.unwrap();
+
+    conn.copy(
+        &PathBuf::from("/tempZone/home/rods/totc.txt"),
+        &PathBuf::from("/tempZone/home/rods/totc_copy.txt"),
+    )
+    .force(true)
+    .verify_checksum(true)
+    .execute()
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn rename_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 1);
+    let mut conn = pool.get().await.unwrap();
+
+    let src = PathBuf::from("/tempZone/home/rods/rename_src.txt");
+    let dst = PathBuf::from("/tempZone/home/rods/rename_dst.txt");
+
+    conn.put_request(&src)
+        .force(true)
+        .execute(b"rename me")
+        .await
+        .unwrap();
+
+    // Left over from an earlier run
+    if conn.try_stat(&dst).await.unwrap().is_some() {
+        DeleteRequest::new(&mut *conn, &dst)
+            .force(true)
+            .execute()
+            .await
+            .unwrap();
+    }
+
+    conn.rename(&src, &dst).await.unwrap();
+
+    assert!(conn.try_stat(&src).await.unwrap().is_none());
+    assert!(conn.try_stat(&dst).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn ensure_collection_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 1);
+    let mut conn = pool.get().await.unwrap();
+
+    let path = PathBuf::from("/tempZone/home/rods/a/b/c");
+
+    conn.ensure_collection(&path).await.unwrap();
+    conn.ensure_collection(&path).await.unwrap();
+}
+
+#[tokio::test]
+async fn chmod_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 1);
+    let mut conn = pool.get().await.unwrap();
+
+    conn.chmod(&PathBuf::from("/tempZone/home/rods/totc.txt"))
+        .grant("public", AccessLevel::Read)
+        .execute()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn effective_access_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 1);
+    let mut conn = pool.get().await.unwrap();
+
+    let path = PathBuf::from("/tempZone/home/rods/totc.txt");
+
+    let acls = conn.list_acls(&path).await.unwrap();
+    assert!(acls.iter().any(|acl| acl.user_name == "rods"));
+
+    let access = conn
+        .effective_access(&path, "rods", "tempZone")
+        .await
+        .unwrap();
+    assert_eq!(access, AccessLevel::Own);
+}
+
+#[tokio::test]
+async fn atomic_metadata_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 1);
+    let mut conn = pool.get().await.unwrap();
+
+    conn.atomic_metadata_operations(AVUTarget::DataObject, "/tempZone/home/rods/totc.txt")
+        .add("project", "archive", "")
+        .add("retention", "10", "years")
+        .execute()
+        .await
+        .unwrap();
+
+    conn.atomic_metadata_operations(AVUTarget::DataObject, "/tempZone/home/rods/totc.txt")
+        .remove("project", "archive", "")
+        .remove("retention", "10", "years")
+        .execute()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn modify_avu_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 1);
+    let mut conn = pool.get().await.unwrap();
+
+    let path = "/tempZone/home/rods/totc.txt".to_string();
+
+    conn.set_avu(
+        AVUTarget::DataObject,
+        path.clone(),
+        AVU::new("status", "draft", ""),
+    )
+    .await
+    .unwrap();
+
+    conn.modify_avu(
+        AVUTarget::DataObject,
+        path.clone(),
+        AVU::new("status", "draft", ""),
+        AVU::new("", "final", ""),
+    )
+    .await
+    .unwrap();
+
+    conn.remove_avu(AVUTarget::DataObject, path, AVU::new("status", "final", ""))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn search_metadata_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 1);
+    let mut conn = pool.get().await.unwrap();
+
+    let path = "/tempZone/home/rods/totc.txt";
+
+    conn.add_avu(
+        AVUTarget::DataObject,
+        path.into(),
+        AVU::new("run", "150", ""),
+    )
+    .await
+    .unwrap();
+
+    let results: Vec<_> = conn
+        .search_metadata()
+        .under(&PathBuf::from("/tempZone/home/rods"))
+        .avu("run", IcatPredicate::Numeric(Comparison::Greater, 100.0))
+        .execute()
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert!(!results.is_empty());
+
+    conn.remove_avu(
+        AVUTarget::DataObject,
+        path.into(),
+        AVU::new("run", "150", ""),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn genquery2_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 1);
+    let mut conn = pool.get().await.unwrap();
+
+    let query = "select COLL_NAME, DATA_NAME where COLL_NAME = '/tempZone/home/rods'";
+
+    let rows: Vec<(String, String)> = conn.genquery2_as(query, None).await.unwrap();
+    assert!(rows.iter().any(|(_, name)| name == "totc.txt"));
+
+    let sql = conn.genquery2_sql(query, None).await.unwrap();
+    assert!(sql.to_lowercase().contains("select"));
+}
+
+#[tokio::test]
+async fn small_portal_upload_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 4);
+
+    let local_path = PathBuf::from("./small_portal.txt");
+    std::fs::write(&local_path, vec![b'a'; 1024]).unwrap();
+
+    // Far under the server's parallel threshold, so it opens no portal
+    let mut context = ParallelTransferContext::new(
+        pool,
+        4,
+        PathBuf::from("/tempZone/home/rods/small_portal.txt"),
+        local_path,
+    )
+    .force_overwrite()
+    .use_portal();
+    context.max_size_before_parallel(0);
+
+    context.upload().await.unwrap();
+}
+
+#[tokio::test]
+async fn directory_upload_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 4);
+
+    let local_path = PathBuf::from("./dir_upload");
+    std::fs::create_dir_all(local_path.join("sub")).unwrap();
+
+    for name in ["a.txt", "b.txt", "c.txt", "sub/d.txt", "sub/e.txt"] {
+        std::fs::write(local_path.join(name), name).unwrap();
+    }
+
+    let remote_path = PathBuf::from("/tempZone/home/rods/dir_upload");
+
+    let summary =
+        ParallelTransferContext::new(pool.clone(), 1, remote_path.clone(), local_path.clone())
+            .recursive()
+            .force_overwrite()
+            .max_concurrent_uploads(2)
+            .upload()
+            .await
+            .unwrap();
+
+    assert_eq!(summary.succeeded.len(), 5);
+    assert!(summary.failed.is_empty());
+
+    // Without force every object already exists, and each one is reported
+    let summary = ParallelTransferContext::new(pool, 1, remote_path, local_path.clone())
+        .recursive()
+        .max_concurrent_uploads(2)
+        .upload()
         .await
         .unwrap();
+
+    assert!(summary.succeeded.is_empty());
+
+    let mut failed: Vec<_> = summary.failed.into_iter().map(|(path, _)| path).collect();
+    failed.sort();
+    assert_eq!(
+        failed,
+        ["a.txt", "b.txt", "c.txt", "sub/d.txt", "sub/e.txt"].map(|name| local_path.join(name))
+    );
+}
+
+#[tokio::test]
+async fn failed_slices_are_retried_and_cleaned_up_test() {
+    let pool = test_pool!(test_manager::<XML, TcpConnector, NativeAuthenticator>(), 4);
+
+    let local_path = PathBuf::from("./totc_failed_slices.txt");
+
+    // No replica lives on this resource, so every attempt at every slice
+    // fails after the local file has been created
+    let mut context = ParallelDownloadContext::new(
+        pool,
+        4,
+        PathBuf::from("/tempZone/home/rods/totc.txt"),
+        local_path.clone(),
+    )
+    .max_size_before_parallel(0)
+    .retry_policy(RetryPolicy::new(
+        2,
+        Duration::from_millis(10),
+        Duration::from_millis(10),
+    ));
+    context.on_resource("noSuchResc".to_owned());
+
+    match context.download().await.unwrap_err() {
+        IrodsError::SlicesFailed {
+            num_slices,
+            failures,
+            ..
+        } => assert_eq!(failures.len(), num_slices),
+        err => panic!("unexpected error: {}", err),
+    }
+
+    assert!(!local_path.exists());
 }